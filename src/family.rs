@@ -56,7 +56,9 @@ pub struct SubFamily {
 ///
 /// See https://ziutek.github.io/2018/05/07/stm32_naming_scheme.html for more details.
 ///
-/// Note that sometimes there are exceptions from this naming rule.
+/// Note that sometimes there are exceptions from this naming rule. The ref name
+/// is decoded by `part_number::PartNumber`, which also documents the known
+/// exceptions.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Mcu {
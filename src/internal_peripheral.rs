@@ -1,12 +1,22 @@
 use std::error::Error;
 use std::path::Path;
 
-use lazy_static::lazy_static;
-use regex::Regex;
 use serde_derive::Deserialize;
 
+use crate::mcu::is_dual_pad_name;
+use crate::signal::{function_to_trait, Signal};
 use crate::utils::load_file;
 
+/// Naming of the pin traits of serial peripherals (USART, UART and LPUART).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SerialPinTraits {
+    /// All serial peripherals share the same traits (e.g. `TxPin<LPUART1>`)
+    Shared,
+    /// Every kind of serial peripheral has its own traits (e.g.
+    /// `LpuartTxPin<LPUART1>`)
+    Separate,
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct PossibleValue {
     #[serde(rename = "$value")]
@@ -20,20 +30,52 @@ pub struct SpecificParameter {
     possible_value: PossibleValue,
 }
 
+/// An AFIO remap option of a signal (STM32F1 only).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemapBlock {
+    /// The name of the remap option (e.g. "USART1_REMAP1")
+    name: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct PinSignal {
     name: String,
-    specific_parameter: SpecificParameter,
+    specific_parameter: Option<SpecificParameter>,
+    remap_block: Option<Vec<RemapBlock>>,
 }
 
 impl PinSignal {
-    fn get_af_value(&self) -> &str {
-        self.specific_parameter
-            .possible_value
-            .val
-            .split('_')
-            .collect::<Vec<_>>()[1]
+    /// Return the alternate functions (e.g. "AF4") through which this signal
+    /// can be routed to the pin.
+    ///
+    /// The STM32F1 has no alternate functions and routes signals with AFIO
+    /// remap options instead, which is indicated by `afio_remap` (see
+    /// `IpGPIO::afio_remap`). In that case the remap options (e.g. "REMAP1")
+    /// are returned, and signals without remap options get "REMAP0", the
+    /// default mapping. Otherwise, signals without an alternate function are
+    /// not returned at all.
+    fn get_af_values(&self, afio_remap: bool) -> Vec<&str> {
+        match self.specific_parameter {
+            Some(ref parameter) => parameter
+                .possible_value
+                .val
+                .split('_')
+                .nth(1)
+                .filter(|af| af.starts_with("AF"))
+                .into_iter()
+                .collect(),
+            None => match self.remap_block {
+                Some(ref blocks) => blocks
+                    .iter()
+                    .filter_map(|block| block.name.rsplit('_').next())
+                    .filter(|remap| remap.starts_with("REMAP"))
+                    .collect(),
+                None if afio_remap => vec!["REMAP0"],
+                None => vec![],
+            },
+        }
     }
 }
 
@@ -51,25 +93,38 @@ pub struct GPIOPin {
 pub struct IpGPIO {
     #[serde(rename = "GPIO_Pin")]
     pub(crate) gpio_pin: Vec<GPIOPin>,
+    /// Whether signals are routed with AFIO remap options instead of
+    /// alternate functions (STM32F1 only), set by `IpGPIO::load`
+    #[serde(skip)]
+    pub(crate) afio_remap: bool,
 }
 
 impl IpGPIO {
     pub fn load<P: AsRef<Path>>(db_dir: P, version: &str) -> Result<Self, Box<dyn Error>> {
-        load_file(db_dir, format!("IP/GPIO-{}_Modes.xml", version))
+        let mut ip: Self = load_file(db_dir, format!("IP/GPIO-{}_Modes.xml", version))?;
+        ip.afio_remap = ip.has_remap_blocks();
+        Ok(ip)
+    }
+
+    /// Return whether any signal in the file has remap blocks.
+    fn has_remap_blocks(&self) -> bool {
+        self.pin_signals()
+            .any(|signal| signal.remap_block.is_some())
     }
-}
 
-lazy_static! {
-    static ref USART_RX: Regex = Regex::new("(LP)?US?ART._RX").unwrap();
-    static ref USART_TX: Regex = Regex::new("(LP)?US?ART._TX").unwrap();
-    static ref SPI_MOSI: Regex = Regex::new("SPI._MOSI").unwrap();
-    static ref SPI_MISO: Regex = Regex::new("SPI._MISO").unwrap();
-    static ref SPI_SCK: Regex = Regex::new("SPI._SCK").unwrap();
-    static ref I2C_SCL: Regex = Regex::new("I2C._SCL").unwrap();
-    static ref I2C_SDA: Regex = Regex::new("I2C._SDA").unwrap();
+    fn pin_signals(&self) -> impl Iterator<Item = &PinSignal> {
+        self.gpio_pin
+            .iter()
+            .flat_map(|pin| pin.pin_signal.iter().flatten())
+    }
 }
 
 impl GPIOPin {
+    /// Return the name of the pin (e.g. "PA0").
+    ///
+    /// The analog-only pad of a dual-pad pin (e.g. "PA0_C" on the STM32H7)
+    /// has the same port and pin number as its GPIO twin, so it keeps its
+    /// "_C" suffix.
     pub fn get_name(&self) -> Option<String> {
         let gpio_pin = self
             .specific_parameter
@@ -78,40 +133,379 @@ impl GPIOPin {
         match gpio_pin {
             Some(v) => {
                 let num = v.possible_value.val.split('_').collect::<Vec<_>>()[2];
-                Some(format!("{}{}", &self.port_name, num))
+                let suffix = if self.is_dual_pad() { "_C" } else { "" };
+                Some(format!("{}{}{}", &self.port_name, num, suffix))
             }
             None => None,
         }
     }
 
-    pub fn get_af_modes(&self) -> Vec<String> {
+    /// Return whether this is the analog-only pad of a dual-pad pin.
+    fn is_dual_pad(&self) -> bool {
+        is_dual_pad_name(&self.name)
+    }
+
+    /// Return the AF modes of this pin (e.g. `AF4: TxPin<USART2>`).
+    ///
+    /// Signals with several roles (e.g. "USART2_RTS_DE") result in one entry
+    /// per role. The traits of serial peripherals are named according to
+    /// `serial_traits`. SAI pins additionally get their block (e.g.
+    /// `SaiSdPin<SAI1, A>`), I2S data pins their role (e.g. `SdPin<I2S2, Ext>`
+    /// for "I2S2_ext_SD" or `SdPin<I2S1, Out>` for "I2S1_SDO").
+    pub fn get_af_modes(&self, afio_remap: bool, serial_traits: SerialPinTraits) -> Vec<String> {
         let mut res = Vec::new();
         if let Some(ref v) = self.pin_signal {
             for sig in v {
-                let per = sig.name.split('_').collect::<Vec<_>>()[0];
-                if USART_RX.is_match(&sig.name) {
-                    res.push(format!("{}: RxPin<{}>", sig.get_af_value(), per));
-                }
-                if USART_TX.is_match(&sig.name) {
-                    res.push(format!("{}: TxPin<{}>", sig.get_af_value(), per));
+                let signal = match Signal::parse(&sig.name) {
+                    Some(signal) => signal,
+                    None => continue,
+                };
+                // The SAI block and the I2S data role (main or extension) are
+                // passed as additional type parameters
+                let (function, type_param) =
+                    match (signal.peripheral.as_str(), signal.function.as_str()) {
+                        ("SAI", function) => {
+                            let mut parts = function.rsplitn(2, '_');
+                            match (parts.next(), parts.next()) {
+                                (Some(block), Some(role)) if block == "A" || block == "B" => {
+                                    (role, Some(block))
+                                }
+                                _ => continue,
+                            }
+                        }
+                        ("I2S", "ext_SD") => ("SD", Some("Ext")),
+                        ("I2S", "SD") => ("SD", Some("Main")),
+                        ("I2S", "SDO") => ("SD", Some("Out")),
+                        ("I2S", "SDI") => ("SD", Some("In")),
+                        (_, function) => (function, None),
+                    };
+                let pin_traits: &[&str] = match (signal.peripheral.as_str(), function) {
+                    ("USART", "RX") | ("UART", "RX") | ("LPUART", "RX") => &["RxPin"],
+                    ("USART", "TX") | ("UART", "TX") | ("LPUART", "TX") => &["TxPin"],
+                    ("USART", "CTS") | ("UART", "CTS") | ("LPUART", "CTS") => &["CtsPin"],
+                    ("USART", "RTS") | ("UART", "RTS") | ("LPUART", "RTS") => &["RtsPin"],
+                    ("USART", "DE") | ("UART", "DE") | ("LPUART", "DE") => &["DePin"],
+                    ("USART", "RTS_DE") | ("UART", "RTS_DE") | ("LPUART", "RTS_DE") => {
+                        &["RtsPin", "DePin"]
+                    }
+                    ("USART", "CK") => &["CkPin"],
+                    ("SPI", "MOSI") => &["MosiPin"],
+                    ("SPI", "MISO") => &["MisoPin"],
+                    ("SPI", "SCK") => &["SckPin"],
+                    ("SPI", "NSS") => &["NssPin"],
+                    ("I2S", "MCK") => &["MckPin"],
+                    ("I2S", "WS") => &["WsPin"],
+                    ("I2S", "SD") => &["SdPin"],
+                    ("I2S", "CK") => &["I2sCkPin"],
+                    ("I2C", "SCL") | ("FMPI2C", "SCL") => &["SclPin"],
+                    ("I2C", "SDA") | ("FMPI2C", "SDA") => &["SdaPin"],
+                    ("I2C", "SMBA") | ("FMPI2C", "SMBA") => &["SmbaPin"],
+                    ("SAI", "SD") => &["SaiSdPin"],
+                    ("SAI", "SCK") => &["SaiSckPin"],
+                    ("SAI", "FS") => &["SaiFsPin"],
+                    ("SAI", "MCLK") => &["SaiMclkPin"],
+                    _ => continue,
+                };
+                let trait_prefix = match (serial_traits, signal.peripheral.as_str()) {
+                    (SerialPinTraits::Separate, "USART") => "Usart",
+                    (SerialPinTraits::Separate, "UART") => "Uart",
+                    (SerialPinTraits::Separate, "LPUART") => "Lpuart",
+                    _ => "",
+                };
+                let type_params = match type_param {
+                    Some(param) => format!("{}, {}", signal.instance_name(), param),
+                    None => signal.instance_name(),
+                };
+                for af in sig.get_af_values(afio_remap) {
+                    for pin_trait in pin_traits {
+                        res.push(format!(
+                            "{}: {}{}<{}>",
+                            af, trait_prefix, pin_trait, type_params
+                        ));
+                    }
                 }
-                if SPI_MOSI.is_match(&sig.name) {
-                    res.push(format!("{}: MosiPin<{}>", sig.get_af_value(), per));
-                }
-                if SPI_MISO.is_match(&sig.name) {
-                    res.push(format!("{}: MisoPin<{}>", sig.get_af_value(), per));
-                }
-                if SPI_SCK.is_match(&sig.name) {
-                    res.push(format!("{}: SckPin<{}>", sig.get_af_value(), per));
+            }
+        }
+        res
+    }
+
+    /// Return all signals of this pin that could be parsed.
+    pub fn get_signals(&self) -> Vec<Signal> {
+        self.pin_signal
+            .iter()
+            .flatten()
+            .filter_map(|sig| Signal::parse(&sig.name))
+            .collect()
+    }
+
+    /// Return the AF modes of all signals matching `filter`, with a trait per
+    /// function (e.g. `AF12: D0Pin<SDMMC1>` for "SDMMC1_D0").
+    pub fn get_function_modes(
+        &self,
+        afio_remap: bool,
+        filter: impl Fn(&Signal) -> bool,
+    ) -> Vec<String> {
+        let mut res = Vec::new();
+        if let Some(ref v) = self.pin_signal {
+            for sig in v {
+                let signal = match Signal::parse(&sig.name) {
+                    Some(signal) if filter(&signal) => signal,
+                    _ => continue,
+                };
+                for af in sig.get_af_values(afio_remap) {
+                    res.push(format!(
+                        "{}: {}<{}>",
+                        af,
+                        function_to_trait(&signal.function),
+                        signal.instance_name()
+                    ));
                 }
-                if I2C_SCL.is_match(&sig.name) {
-                    res.push(format!("{}: SclPin<{}>", sig.get_af_value(), per));
+            }
+        }
+        res
+    }
+
+    /// Return the touch sensing controller modes of this pin.
+    ///
+    /// Every I/O of a TSC group can be used either as a channel or as the
+    /// sampling capacitor of its group, so both `TscPin<G5, 3>` and
+    /// `TscSamplingPin<G5, 3>` are returned for "TSC_G5_IO3". The
+    /// synchronization input is returned as `TscSyncPin<TSC>`.
+    pub fn get_tsc_modes(&self, afio_remap: bool) -> Vec<String> {
+        let mut res = Vec::new();
+        if let Some(ref v) = self.pin_signal {
+            for sig in v {
+                let signal = match Signal::parse(&sig.name) {
+                    Some(signal) if signal.peripheral == "TSC" => signal,
+                    _ => continue,
+                };
+                if signal.function == "SYNC" {
+                    for af in sig.get_af_values(afio_remap) {
+                        res.push(format!("{}: TscSyncPin<{}>", af, signal.instance_name()));
+                    }
+                    continue;
                 }
-                if I2C_SDA.is_match(&sig.name) {
-                    res.push(format!("{}: SdaPin<{}>", sig.get_af_value(), per));
+                let (group, io) = match (signal.function.split('_').next(), signal.index) {
+                    (Some(group), Some(io)) if group.starts_with('G') => (group, io),
+                    _ => continue,
+                };
+                for af in sig.get_af_values(afio_remap) {
+                    for pin_trait in &["TscPin", "TscSamplingPin"] {
+                        res.push(format!("{}: {}<{}, {}>", af, pin_trait, group, io));
+                    }
                 }
             }
         }
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin_signal(xml: &str) -> PinSignal {
+        serde_xml_rs::deserialize(xml.as_bytes()).unwrap()
+    }
+
+    /// Build the GPIO pin "PA0" with the given signals and their GPIO_AF
+    /// values (e.g. `("USART2_TX", "GPIO_AF4_USART2")`).
+    fn gpio_pin(signals: &[(&str, &str)]) -> GPIOPin {
+        let pin_signals = signals
+            .iter()
+            .map(|(name, af)| {
+                format!(
+                    r#"<PinSignal Name="{}">
+                        <SpecificParameter Name="GPIO_AF">
+                            <PossibleValue>{}</PossibleValue>
+                        </SpecificParameter>
+                    </PinSignal>"#,
+                    name, af
+                )
+            })
+            .collect::<String>();
+        let xml = format!(
+            r#"<GPIO_Pin PortName="PA" Name="PA0">
+                <SpecificParameter Name="GPIO_Pin">
+                    <PossibleValue>GPIO_PIN_0</PossibleValue>
+                </SpecificParameter>
+                {}
+            </GPIO_Pin>"#,
+            pin_signals
+        );
+        serde_xml_rs::deserialize(xml.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_get_af_values() {
+        let signal = pin_signal(
+            r#"<PinSignal Name="USART2_TX">
+                <SpecificParameter Name="GPIO_AF">
+                    <PossibleValue>GPIO_AF4_USART2</PossibleValue>
+                </SpecificParameter>
+            </PinSignal>"#,
+        );
+        assert_eq!(signal.get_af_values(false), vec!["AF4"]);
+    }
+
+    #[test]
+    fn test_get_af_values_remap() {
+        let signal = pin_signal(
+            r#"<PinSignal Name="USART1_TX">
+                <RemapBlock Name="USART1_REMAP1">
+                    <SpecificParameter Name="GPIO_AF">
+                        <PossibleValue>__HAL_AFIO_REMAP_USART1_ENABLE</PossibleValue>
+                    </SpecificParameter>
+                </RemapBlock>
+            </PinSignal>"#,
+        );
+        assert_eq!(signal.get_af_values(false), vec!["REMAP1"]);
+
+        let signal = pin_signal(
+            r#"<PinSignal Name="TIM2_CH3">
+                <RemapBlock Name="TIM2_REMAP0" DefaultRemap="true"/>
+                <RemapBlock Name="TIM2_REMAP1">
+                    <SpecificParameter Name="GPIO_AF">
+                        <PossibleValue>__HAL_AFIO_REMAP_TIM2_PARTIAL_1</PossibleValue>
+                    </SpecificParameter>
+                </RemapBlock>
+            </PinSignal>"#,
+        );
+        assert_eq!(signal.get_af_values(false), vec!["REMAP0", "REMAP1"]);
+
+        let signal = pin_signal(r#"<PinSignal Name="SPI2_MOSI"/>"#);
+        assert!(signal.get_af_values(false).is_empty());
+        assert_eq!(signal.get_af_values(true), vec!["REMAP0"]);
+    }
+
+    #[test]
+    fn test_has_remap_blocks() {
+        let ip_gpio = |xml: &str| -> IpGPIO { serde_xml_rs::deserialize(xml.as_bytes()).unwrap() };
+        let ip = ip_gpio(
+            r#"<IP>
+                <GPIO_Pin PortName="PB" Name="PB10">
+                    <SpecificParameter Name="GPIO_Pin">
+                        <PossibleValue>GPIO_PIN_10</PossibleValue>
+                    </SpecificParameter>
+                    <PinSignal Name="SPI2_MOSI"/>
+                    <PinSignal Name="USART3_TX">
+                        <RemapBlock Name="USART3_REMAP0" DefaultRemap="true"/>
+                    </PinSignal>
+                </GPIO_Pin>
+            </IP>"#,
+        );
+        assert!(ip.has_remap_blocks());
+
+        let ip = ip_gpio(
+            r#"<IP>
+                <GPIO_Pin PortName="PA" Name="PA0">
+                    <SpecificParameter Name="GPIO_Pin">
+                        <PossibleValue>GPIO_PIN_0</PossibleValue>
+                    </SpecificParameter>
+                    <PinSignal Name="ADC_IN0"/>
+                </GPIO_Pin>
+            </IP>"#,
+        );
+        assert!(!ip.has_remap_blocks());
+    }
+
+    #[test]
+    fn test_get_tsc_modes() {
+        let pin = gpio_pin(&[
+            ("TSC_G5_IO3", "GPIO_AF9_TSC"),
+            ("TSC_SYNC", "GPIO_AF9_TSC"),
+            ("USART2_TX", "GPIO_AF4_USART2"),
+        ]);
+        assert_eq!(pin.get_name(), Some("PA0".to_string()));
+        assert_eq!(
+            pin.get_tsc_modes(false),
+            vec![
+                "AF9: TscPin<G5, 3>",
+                "AF9: TscSamplingPin<G5, 3>",
+                "AF9: TscSyncPin<TSC>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_af_modes_roles() {
+        let pin = gpio_pin(&[
+            ("USART2_CTS", "GPIO_AF4_USART2"),
+            ("USART2_RTS_DE", "GPIO_AF4_USART2"),
+            ("USART1_CK", "GPIO_AF4_USART1"),
+            ("LPUART1_DE", "GPIO_AF6_LPUART1"),
+            ("SPI1_NSS", "GPIO_AF0_SPI1"),
+            ("I2S2_MCK", "GPIO_AF5_SPI2"),
+            ("I2S2_WS", "GPIO_AF5_SPI2"),
+            ("I2S2_CK", "GPIO_AF5_SPI2"),
+            ("I2C1_SMBA", "GPIO_AF1_I2C1"),
+        ]);
+        assert_eq!(
+            pin.get_af_modes(false, SerialPinTraits::Shared),
+            vec![
+                "AF4: CtsPin<USART2>",
+                "AF4: RtsPin<USART2>",
+                "AF4: DePin<USART2>",
+                "AF4: CkPin<USART1>",
+                "AF6: DePin<LPUART1>",
+                "AF0: NssPin<SPI1>",
+                "AF5: MckPin<I2S2>",
+                "AF5: WsPin<I2S2>",
+                "AF5: I2sCkPin<I2S2>",
+                "AF1: SmbaPin<I2C1>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_af_modes_serial_traits() {
+        let pin = gpio_pin(&[
+            ("USART1_TX", "GPIO_AF7_USART1"),
+            ("UART4_TX", "GPIO_AF8_UART4"),
+            ("LPUART1_TX", "GPIO_AF8_LPUART1"),
+            ("SPI1_SCK", "GPIO_AF5_SPI1"),
+        ]);
+        assert_eq!(
+            pin.get_af_modes(false, SerialPinTraits::Shared),
+            vec![
+                "AF7: TxPin<USART1>",
+                "AF8: TxPin<UART4>",
+                "AF8: TxPin<LPUART1>",
+                "AF5: SckPin<SPI1>",
+            ]
+        );
+        assert_eq!(
+            pin.get_af_modes(false, SerialPinTraits::Separate),
+            vec![
+                "AF7: UsartTxPin<USART1>",
+                "AF8: UartTxPin<UART4>",
+                "AF8: LpuartTxPin<LPUART1>",
+                "AF5: SckPin<SPI1>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_af_modes_sai_i2s() {
+        let pin = gpio_pin(&[
+            ("SAI1_SD_A", "GPIO_AF6_SAI1"),
+            ("SAI1_FS_B", "GPIO_AF6_SAI1"),
+            ("SAI1_CK1", "GPIO_AF2_SAI1"),
+            ("I2S2_SD", "GPIO_AF5_SPI2"),
+            ("I2S2_ext_SD", "GPIO_AF6_I2S2ext"),
+            ("I2S1_SDO", "GPIO_AF5_SPI1"),
+            ("I2S1_SDI", "GPIO_AF5_SPI1"),
+        ]);
+        assert_eq!(
+            pin.get_af_modes(false, SerialPinTraits::Shared),
+            vec![
+                "AF6: SaiSdPin<SAI1, A>",
+                "AF6: SaiFsPin<SAI1, B>",
+                "AF5: SdPin<I2S2, Main>",
+                "AF6: SdPin<I2S2, Ext>",
+                "AF5: SdPin<I2S1, Out>",
+                "AF5: SdPin<I2S1, In>",
+            ]
+        );
+    }
+}
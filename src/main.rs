@@ -1,13 +1,22 @@
-use std::{collections::HashMap, env, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::Path,
+};
 
 use alphanumeric_sort::compare_str;
 use clap::{App, Arg};
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use crate::internal_peripheral::SerialPinTraits;
+use crate::signal::{function_to_trait, Signal};
+
 mod family;
 mod internal_peripheral;
 mod mcu;
+mod part_number;
+mod signal;
 mod utils;
 
 #[derive(Debug, PartialEq)]
@@ -15,13 +24,70 @@ enum GenerateTarget {
     Features,
     PinMappings,
     EepromSizes,
+    TemperatureRanges,
+    Cores,
+    Limits,
+    DeviceIds,
+    GpioPorts,
+    PinCounts,
+    AdcPins,
+    MemoryPins,
+    PhyPins,
+    ExtiPins,
+    ReservedPins,
+}
+
+/// Naming of the package features.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum PackageFeatureStyle {
+    /// The lowercase package name (e.g. "lqfp48")
+    Lowercase,
+    /// The lowercase package name with a prefix (e.g. "package-lqfp48")
+    Prefixed,
 }
 
 lazy_static! {
     // Note: Version >1.0 is not currently supported
     static ref GPIO_VERSION: Regex = Regex::new("^([^_]*)_gpio_v1_0$").unwrap();
+    static ref IP_VERSION: Regex = Regex::new("^(.+)_([a-z0-9]+)_(v\\d+_\\d+)$").unwrap();
 }
 
+/// Families with Cortex-M4 or Cortex-M33 cores that have no FPU.
+const NO_FPU_FAMILIES: &[&str] = &["STM32WL"];
+
+/// IP names that do not correspond to a peripheral instance.
+///
+/// These are either software components (middlewares) configurable through
+/// CubeMX, peripherals that are present on every MCU, or peripherals that are
+/// already covered by other features. The core peripherals (e.g. "CORTEX_M4")
+/// are skipped as well, see `is_peripheral_ip`.
+const NON_PERIPHERAL_IPS: &[&str] = &[
+    "DEBUG",
+    "DMA",
+    "FATFS",
+    "FILEX",
+    "FREERTOS",
+    "GPIO",
+    "GRAPHICS",
+    "LIBJPEG",
+    "LEVELX",
+    "LWIP",
+    "MBEDTLS",
+    "NETXDUO",
+    "NVIC",
+    "OPENAMP",
+    "PDM2PCM",
+    "PWR",
+    "RCC",
+    "STM32_WPAN",
+    "SYS",
+    "THREADX",
+    "TOUCHSENSING",
+    "USBX",
+    "USB_DEVICE",
+    "USB_HOST",
+];
+
 /// Convert a GPIO IP version (e.g. "STM32L152x8_gpio_v1_0") to a feature name
 /// (e.g. "io-STM32L152x8").
 fn gpio_version_to_feature(version: &str) -> Result<String, String> {
@@ -32,6 +98,221 @@ fn gpio_version_to_feature(version: &str) -> Result<String, String> {
     }
 }
 
+/// Convert an IP version to a feature name, prefixed with the IP name.
+///
+/// The version is used without the "_Cube" suffix. For versions following the
+/// GPIO naming scheme, the repeated IP name is dropped, but the version number
+/// is kept:
+///
+/// - ("RCC", "STM32L051_rcc_v1_0") -> "rcc-STM32L051_v1_0"
+/// - ("USART", "sci3_v1_1_Cube") -> "usart-sci3_v1_1"
+/// - ("TIM", "tim1_8_v1_0") -> "tim-tim1_8_v1_0"
+fn ip_version_to_feature(ip_name: &str, version: &str) -> String {
+    let ip_name = ip_name.to_lowercase();
+    let version = version.trim_end_matches("_Cube");
+    match IP_VERSION.captures(version) {
+        Some(ref captures) if captures[2] == ip_name => {
+            format!("{}-{}_{}", ip_name, &captures[1], &captures[3])
+        }
+        _ => format!("{}-{}", ip_name, version),
+    }
+}
+
+/// Return the IP version features of a MCU for the selected IPs.
+fn ip_version_features(mcu_dat: &mcu::Mcu, ip_names: &[String]) -> Vec<String> {
+    let mut features = mcu_dat
+        .get_ips()
+        .filter(|ip| ip_names.iter().any(|name| name == ip.get_name()))
+        .map(|ip| ip_version_to_feature(ip.get_name(), ip.get_version()))
+        .collect::<Vec<_>>();
+    features.sort();
+    features.dedup();
+    features
+}
+
+/// Get the package feature for a certain package (e.g. "LQFP48").
+fn package_to_feature(package: &str, style: PackageFeatureStyle) -> String {
+    match style {
+        PackageFeatureStyle::Lowercase => package.to_lowercase(),
+        PackageFeatureStyle::Prefixed => format!("package-{}", package.to_lowercase()),
+    }
+}
+
+/// Convert a line or subfamily name (e.g. "STM32L0x1" or "STM32F0x0 Value
+/// Line") to a feature name (e.g. "stm32l0x1" or "stm32f0x0").
+fn line_to_feature(line: &str) -> Option<String> {
+    let name = line.split_whitespace().next()?;
+    Some(
+        name.to_lowercase()
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | '0'..='9' | '-' | '_' | '+' => c,
+                _ => '-',
+            })
+            .collect(),
+    )
+}
+
+/// Parse the line feature overrides.
+///
+/// Every non-empty line that is not a comment (starting with `#`) has the
+/// form `<regex> = <feature>`. The regex is matched against the MCU ref name.
+fn parse_line_overrides(content: &str) -> Result<Vec<(Regex, String)>, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.splitn(2, '=').map(str::trim);
+            match (parts.next(), parts.next()) {
+                (Some(pattern), Some(feature)) if !pattern.is_empty() && !feature.is_empty() => {
+                    let regex = Regex::new(pattern)
+                        .map_err(|e| format!("Invalid pattern {:?}: {}", pattern, e))?;
+                    Ok((regex, feature.to_string()))
+                }
+                _ => Err(format!("Invalid line override {:?}", line)),
+            }
+        })
+        .collect()
+}
+
+/// Get the peripheral feature for a certain peripheral instance (e.g.
+/// "USART4" -> "has-usart4").
+fn peripheral_instance_to_feature(instance: &str) -> String {
+    format!("has-{}", instance.to_lowercase())
+}
+
+/// Return whether an IP name corresponds to a peripheral instance, i.e. it is
+/// neither listed in `NON_PERIPHERAL_IPS` nor a core (e.g. "CORTEX_M7").
+fn is_peripheral_ip(name: &str) -> bool {
+    !NON_PERIPHERAL_IPS.contains(&name) && !name.starts_with("CORTEX_M")
+}
+
+/// Return the names of all peripheral instances of a MCU.
+fn peripheral_instances(mcu_dat: &mcu::Mcu) -> Vec<&str> {
+    let mut instances = mcu_dat
+        .get_ips()
+        .filter(|ip| is_peripheral_ip(ip.get_name()))
+        .map(|ip| ip.get_instance_name())
+        .collect::<Vec<_>>();
+    instances.sort_by(|a, b| compare_str(a, b));
+    instances.dedup();
+    instances
+}
+
+/// Return the flash size of a MCU in kbytes.
+///
+/// The flash size is looked up in the flash variants of the MCU XML file (see
+/// `part_number::expand_flash_variants`), falling back to the flash size in
+/// `families.xml`.
+fn flash_size(mcu: &family::Mcu, mcu_dat: &mcu::Mcu) -> Option<u32> {
+    part_number::expand_flash_variants(&mcu.name, &mcu_dat.get_flash_sizes())
+        .into_iter()
+        .find(|(part, _)| part == &mcu.ref_name)
+        .and_then(|(_, size)| size)
+        .or_else(|| mcu.flash_size())
+}
+
+/// Get the temperature range feature for the maximum temperature of all
+/// orderable grades.
+fn temperature_range_to_feature(max: i32) -> String {
+    format!("temp-range-max-{}", max)
+}
+
+/// Get the temperature grade feature for a certain maximum temperature.
+fn temperature_grade_to_feature(max: i32) -> String {
+    format!("temp-grade-{}", max)
+}
+
+/// Get the alias of an ordering code with a temperature grade (e.g.
+/// "STM32L071KBT3"), which depends on the MCU alias of its ref name.
+fn temperature_grade_alias(ordering_code: &str, ref_name: &str, max: i32) -> String {
+    format!(
+        "mcu-{} = [\"mcu-{}\", \"{}\"]",
+        ordering_code,
+        ref_name,
+        temperature_grade_to_feature(max)
+    )
+}
+
+/// Convert a core (e.g. "Arm Cortex-M0+") to a feature name (e.g.
+/// "cortex-m0plus") and the matching Rust target triple.
+///
+/// Cortex-M4, Cortex-M7 and Cortex-M33 cores of STM32 MCUs always have an FPU
+/// (indicated by an "f" suffix), except for the families in
+/// `NO_FPU_FAMILIES`.
+fn core_to_feature(core: &str, mcu_family: &str) -> Option<(String, &'static str)> {
+    let name = core.to_lowercase();
+    let name = name.trim_start_matches("arm ").replace('+', "plus");
+    let fpu = !NO_FPU_FAMILIES.contains(&mcu_family);
+    let target = match (name.as_str(), fpu) {
+        ("cortex-m0", _) | ("cortex-m0plus", _) => "thumbv6m-none-eabi",
+        ("cortex-m3", _) => "thumbv7m-none-eabi",
+        ("cortex-m4", true) | ("cortex-m7", _) => "thumbv7em-none-eabihf",
+        ("cortex-m4", false) => "thumbv7em-none-eabi",
+        ("cortex-m23", _) => "thumbv8m.base-none-eabi",
+        ("cortex-m33", true) => "thumbv8m.main-none-eabihf",
+        ("cortex-m33", false) => "thumbv8m.main-none-eabi",
+        ("cortex-a7", _) => "armv7a-none-eabihf",
+        _ => return None,
+    };
+    let feature = if target.ends_with("hf") && name.starts_with("cortex-m") {
+        format!("{}f", name)
+    } else {
+        name
+    };
+    Some((feature, target))
+}
+
+/// Return the core features and target triples of a MCU.
+fn core_features(mcu_dat: &mcu::Mcu, mcu_family: &str) -> Vec<(String, &'static str)> {
+    let mut features = mcu_dat
+        .get_cores()
+        .iter()
+        .filter_map(|core| core_to_feature(core, mcu_family))
+        .collect::<Vec<_>>();
+    features.dedup();
+    features
+}
+
+/// Get the SYSCLK feature for a certain maximum frequency in MHz.
+fn frequency_to_feature(frequency: u32) -> String {
+    format!("sysclk-max-{}", frequency)
+}
+
+/// Get the supply voltage feature for a certain voltage range in mV.
+fn voltage_range_to_feature((min, max): (u32, u32)) -> String {
+    format!("vdd-{}-{}", min, max)
+}
+
+/// Return the DBGMCU_IDCODE DEV_ID of a die (e.g. "DIE447" -> 0x447).
+fn die_to_device_id(die: &str) -> Option<u16> {
+    if !die.starts_with("DIE") {
+        return None;
+    }
+    u16::from_str_radix(&die[3..], 16).ok()
+}
+
+/// Get the die feature for a certain die (e.g. "DIE447" -> "die-447").
+fn die_to_feature(die: &str) -> String {
+    format!("die-{}", die.trim_start_matches("DIE").to_lowercase())
+}
+
+/// Get the GPIO port feature for a certain port (e.g. 'F' -> "gpio-portf").
+fn gpio_port_to_feature(port: char) -> String {
+    format!("gpio-port{}", port.to_ascii_lowercase())
+}
+
+/// Get the pin count feature for a certain package pin count.
+fn pin_count_to_feature(count: u32) -> String {
+    format!("pins-{}", count)
+}
+
+/// Get the I/O count feature for a certain number of I/Os.
+fn io_count_to_feature(count: u32) -> String {
+    format!("io-count-{}", count)
+}
+
 /// Get the EEPROM size feature for a certain size.
 fn eeprom_size_to_feature(size: u32) -> String {
     format!("eeprom-{}", size)
@@ -47,6 +328,56 @@ fn ram_size_to_feature(size: u32) -> String {
     format!("ram-{}", size)
 }
 
+/// Data of all MCUs of a family, aggregated for the generators.
+///
+/// Unless noted otherwise, the keys of the maps are values of a property (e.g.
+/// flash sizes), while the values are Vecs of MCU ref names.
+#[derive(Default)]
+struct McuMaps<'a> {
+    /// MCU ref names to the corresponding `family::Mcu` and `mcu::Mcu`
+    mcus: HashMap<String, (&'a family::Mcu, mcu::Mcu)>,
+    /// GPIO peripheral versions (e.g. "STM32L051_gpio_v1_0")
+    gpio: HashMap<String, Vec<String>>,
+    /// GPIO ports (e.g. 'A')
+    gpio_ports: HashMap<char, Vec<String>>,
+    /// MCU ref names to line features
+    lines: HashMap<String, String>,
+    /// Core features and the matching target triples
+    cores: HashMap<(String, &'static str), Vec<String>>,
+    /// MCU ref names to package features, only filled if package features are
+    /// enabled
+    packages: HashMap<String, String>,
+    /// Package pin counts
+    pin_counts: HashMap<u32, Vec<String>>,
+    /// Numbers of I/Os
+    io_counts: HashMap<u32, Vec<String>>,
+    /// EEPROM sizes in bytes
+    eeprom_sizes: HashMap<u32, Vec<String>>,
+    /// Flash sizes in kbytes
+    flash_sizes: HashMap<u32, Vec<String>>,
+    /// RAM sizes in kbytes
+    ram_sizes: HashMap<u32, Vec<String>>,
+    /// Maximum temperatures in °C
+    temperatures: HashMap<i32, Vec<String>>,
+    /// Ordering codes with a temperature grade (e.g. "STM32L071KBT3") to
+    /// their ref name and maximum temperature in °C, only filled if
+    /// temperature grades are enabled
+    temperature_grades: HashMap<String, (String, i32)>,
+    /// Die names (e.g. "DIE447")
+    dies: HashMap<String, Vec<String>>,
+    /// Maximum CPU frequencies in MHz
+    frequencies: HashMap<u32, Vec<String>>,
+    /// Supply voltage ranges (min, max) in mV
+    voltages: HashMap<(u32, u32), Vec<String>>,
+    /// Peripheral instance names (e.g. "USART4")
+    peripherals: HashMap<String, Vec<String>>,
+    /// IP version features (e.g. "rcc-STM32L051_v1_0") of the IPs selected
+    /// on the command line
+    ip_versions: HashMap<String, Vec<String>>,
+    /// MCU ref names to the features their MCU alias depends on
+    aliases: HashMap<String, Vec<String>>,
+}
+
 fn main() -> Result<(), String> {
     let args = App::new("cube-parse")
         .version(env!("CARGO_PKG_VERSION"))
@@ -63,7 +394,22 @@ fn main() -> Result<(), String> {
             Arg::with_name("generate")
                 .help("What to generate")
                 .takes_value(true)
-                .possible_values(&["features", "pin_mappings", "eeprom_sizes"])
+                .possible_values(&[
+                    "features",
+                    "pin_mappings",
+                    "eeprom_sizes",
+                    "temperature_ranges",
+                    "cores",
+                    "limits",
+                    "device_ids",
+                    "gpio_ports",
+                    "pin_counts",
+                    "adc_pins",
+                    "memory_pins",
+                    "phy_pins",
+                    "exti_pins",
+                    "reserved_pins",
+                ])
                 .required(true),
         )
         .arg(
@@ -72,6 +418,64 @@ fn main() -> Result<(), String> {
                 .takes_value(true)
                 .required(true),
         )
+        .arg(
+            Arg::with_name("line_overrides")
+                .long("line-overrides")
+                .help(
+                    "Path to a file with lines of the form \"<regex> = <feature>\", \
+                     overriding the line feature of matching MCU ref names",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("package_features")
+                .long("package-features")
+                .help(
+                    "Generate package features with the given naming \
+                     (default for STM32L0: lowercase)",
+                )
+                .takes_value(true)
+                .possible_values(&["lowercase", "prefixed"]),
+        )
+        .arg(
+            Arg::with_name("ip_versions")
+                .long("ip-versions")
+                .help(
+                    "Comma separated list of IPs (e.g. \"USART,RCC\") for which \
+                     to generate IP version features",
+                )
+                .takes_value(true)
+                .use_delimiter(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("serial_pin_traits")
+                .long("serial-pin-traits")
+                .help(
+                    "Whether USART, UART and LPUART pins share the same traits \
+                     or get separate traits (e.g. \"LpuartTxPin\")",
+                )
+                .takes_value(true)
+                .possible_values(&["shared", "separate"])
+                .default_value("shared"),
+        )
+        .arg(
+            Arg::with_name("temperature_grades")
+                .long("temperature-grades")
+                .help(
+                    "Generate temperature grade features and MCU aliases for \
+                     ordering codes with a temperature code (e.g. \"STM32L071KBT3\")",
+                ),
+        )
+        .arg(
+            Arg::with_name("reserved_pin_warnings")
+                .long("reserved-pin-warnings")
+                .help(
+                    "Add a warning comment to pin mappings of debug, oscillator \
+                     and boot pins",
+                ),
+        )
         .get_matches();
 
     // Process args
@@ -81,11 +485,48 @@ fn main() -> Result<(), String> {
         "features" => GenerateTarget::Features,
         "pin_mappings" => GenerateTarget::PinMappings,
         "eeprom_sizes" => GenerateTarget::EepromSizes,
+        "temperature_ranges" => GenerateTarget::TemperatureRanges,
+        "cores" => GenerateTarget::Cores,
+        "limits" => GenerateTarget::Limits,
+        "device_ids" => GenerateTarget::DeviceIds,
+        "gpio_ports" => GenerateTarget::GpioPorts,
+        "pin_counts" => GenerateTarget::PinCounts,
+        "adc_pins" => GenerateTarget::AdcPins,
+        "memory_pins" => GenerateTarget::MemoryPins,
+        "phy_pins" => GenerateTarget::PhyPins,
+        "exti_pins" => GenerateTarget::ExtiPins,
+        "reserved_pins" => GenerateTarget::ReservedPins,
         _ => unreachable!(),
     };
+    let ip_names = args
+        .values_of("ip_versions")
+        .map(|values| values.map(str::to_uppercase).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let package_style = match args.value_of("package_features") {
+        Some("lowercase") => Some(PackageFeatureStyle::Lowercase),
+        Some("prefixed") => Some(PackageFeatureStyle::Prefixed),
+        Some(_) => unreachable!(),
+        // The stm32l0xx-hal has package based features
+        None if mcu_family == "STM32L0" => Some(PackageFeatureStyle::Lowercase),
+        None => None,
+    };
+    let serial_traits = match args.value_of("serial_pin_traits").unwrap() {
+        "shared" => SerialPinTraits::Shared,
+        "separate" => SerialPinTraits::Separate,
+        _ => unreachable!(),
+    };
+    let temperature_grades = args.is_present("temperature_grades");
+    let reserved_pin_warnings = args.is_present("reserved_pin_warnings");
+    let line_overrides = match args.value_of("line_overrides") {
+        Some(path) => parse_line_overrides(
+            &fs::read_to_string(path)
+                .map_err(|e| format!("Could not read line overrides: {}", e))?,
+        )?,
+        None => vec![],
+    };
 
     // Load families
-    let families = family::Families::load(&db_dir)
+    let families = family::Families::load(db_dir)
         .map_err(|e| format!("Could not load families XML: {}", e))?;
 
     // Find target family
@@ -94,69 +535,104 @@ fn main() -> Result<(), String> {
         .find(|v| v.name == mcu_family)
         .ok_or_else(|| format!("Could not find family {}", mcu_family))?;
 
-    // MCU map
-    //
-    // This maps a MCU ref name to the corresponding `mcu::Mcu` instance.
-    let mut mcu_map: HashMap<String, (&family::Mcu, mcu::Mcu)> = HashMap::new();
-
-    // GPIO map
-    //
-    // The keys of this map are GPIO peripheral version strings (e.g.
-    // "STM32L051_gpio_v1_0"), while the value is a Vec of MCU ref names.
-    let mut mcu_gpio_map: HashMap<String, Vec<String>> = HashMap::new();
-
-    // Package map
-    //
-    // The keys of this map are MCU ref names, the values are package names.
-    let mut mcu_package_map: HashMap<String, String> = HashMap::new();
-
-    // EEPROM size map
-    //
-    // The keys of this map are EEPROM sizes, the values are Vecs of MCU ref names.
-    let mut mcu_eeprom_size_map: HashMap<u32, Vec<String>> = HashMap::new();
-
-    // Flash size map
-    //
-    // The keys of this map are flash sizes, the values are Vecs of MCU ref names.
-    let mut mcu_flash_size_map: HashMap<u32, Vec<String>> = HashMap::new();
-
-    // RAM size map
-    //
-    // The keys of this map are RAM sizes, the values are Vecs of MCU ref names.
-    let mut mcu_ram_size_map: HashMap<u32, Vec<String>> = HashMap::new();
-
-    // Iterate through subfamilies, then through MCUs. Fill the maps above with
-    // aggregated data.
+    let mut maps = McuMaps::default();
+
+    // Iterate through subfamilies, then through MCUs. Fill the maps with
+    // aggregated data. The features each MCU alias depends on are collected in
+    // the same order as they are listed in the alias.
     for sf in family {
         for mcu in sf {
             // Load MCU data from the XML files
-            let mcu_dat = mcu::Mcu::load(&db_dir, &mcu.name)
+            let mcu_dat = mcu::Mcu::load(db_dir, &mcu.name)
                 .map_err(|e| format!("Could not load MCU data for mcu {}: {}", &mcu.name, e))?;
 
+            let mut dependencies = vec![];
+
+            // Fill line map
+            let line_feature = line_overrides
+                .iter()
+                .find(|(regex, _)| regex.is_match(&mcu.ref_name))
+                .map(|(_, feature)| feature.clone())
+                .or_else(|| line_to_feature(mcu_dat.get_line().unwrap_or(&sf.name)));
+            if let Some(feature) = line_feature {
+                dependencies.push(feature.clone());
+                maps.lines.insert(mcu.ref_name.clone(), feature);
+            }
+
+            // Fill core map
+            for core in mcu_dat.get_cores() {
+                if core_to_feature(core, mcu_family).is_none() {
+                    eprintln!("Warning: Unknown core {:?} of {}", core, mcu.ref_name);
+                }
+            }
+            for core in core_features(&mcu_dat, mcu_family) {
+                dependencies.push(core.0.clone());
+                maps.cores
+                    .entry(core)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            // Fill package map
+            if let Some(style) = package_style {
+                let feature = package_to_feature(&mcu.package_name, style);
+                dependencies.push(feature.clone());
+                maps.packages.insert(mcu.ref_name.clone(), feature);
+            }
+
             // Fill GPIO map
             let gpio_version = mcu_dat.get_ip("GPIO").unwrap().get_version().to_string();
-            mcu_gpio_map
+            if let Ok(feature) = gpio_version_to_feature(&gpio_version) {
+                dependencies.push(feature);
+            }
+            maps.gpio
                 .entry(gpio_version)
                 .or_insert(vec![])
                 .push(mcu.ref_name.clone());
 
-            // Fill package map
-            if mcu_family == "STM32L0" {
-                // The stm32l0xx-hal has package based features
-                mcu_package_map.insert(mcu.ref_name.clone(), mcu.package_name.clone());
+            // Fill GPIO port map
+            for port in mcu_dat.get_gpio_ports().keys() {
+                dependencies.push(gpio_port_to_feature(*port));
+                maps.gpio_ports
+                    .entry(*port)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
             }
 
             // Fill EEPROM size map
             if let Some(size) = mcu_dat.get_eeprom_size() {
-                mcu_eeprom_size_map
+                dependencies.push(eeprom_size_to_feature(size));
+                maps.eeprom_sizes
                     .entry(size)
                     .or_insert(vec![])
                     .push(mcu.ref_name.clone());
             }
 
+            // Check the part number against the database
+            if generate == GenerateTarget::Features {
+                match part_number::PartNumber::parse(&mcu.ref_name) {
+                    Some(part) => {
+                        for warning in part.check(&mcu.package_name, &mcu_dat.get_flash_sizes()) {
+                            eprintln!("Warning: {}: {}", mcu.ref_name, warning);
+                        }
+                    }
+                    None => eprintln!("Warning: Could not decode part number {}", mcu.ref_name),
+                }
+            }
+
             // Fill flash size map
-            if let Some(flash_size) = mcu.flash_size() {
-                mcu_flash_size_map
+            let flash_size = flash_size(mcu, &mcu_dat);
+            if let (Some(size), Some(listed_size)) = (flash_size, mcu.flash_size()) {
+                if size != listed_size {
+                    eprintln!(
+                        "Warning: Flash size of {} is {} kbytes, but families.xml lists {} kbytes",
+                        mcu.ref_name, size, listed_size
+                    );
+                }
+            }
+            if let Some(flash_size) = flash_size {
+                dependencies.push(flash_size_to_feature(flash_size));
+                maps.flash_sizes
                     .entry(flash_size)
                     .or_insert(vec![])
                     .push(mcu.ref_name.clone());
@@ -164,68 +640,161 @@ fn main() -> Result<(), String> {
 
             // Fill RAM size map
             if let Some(ram_size) = mcu.ram_size() {
-                mcu_ram_size_map
+                dependencies.push(ram_size_to_feature(ram_size));
+                maps.ram_sizes
                     .entry(ram_size)
                     .or_insert(vec![])
                     .push(mcu.ref_name.clone());
             }
 
-            mcu_map.insert(mcu.ref_name.clone(), (mcu, mcu_dat));
+            // Fill pin count map
+            if let Some(count) = part_number::package_pin_count(&mcu.package_name) {
+                dependencies.push(pin_count_to_feature(count));
+                maps.pin_counts
+                    .entry(count)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            // Fill I/O count map
+            if let Some(count) = mcu_dat.get_io_count() {
+                dependencies.push(io_count_to_feature(count));
+                maps.io_counts
+                    .entry(count)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            // Fill temperature map
+            //
+            // Ref names in the database never contain a temperature code, so
+            // the range from the MCU XML file is used. It covers all
+            // temperature grades that can be ordered.
+            if let Some(range) = mcu_dat.get_temperature_range() {
+                dependencies.push(temperature_range_to_feature(range.1));
+                maps.temperatures
+                    .entry(range.1)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+
+                // Fill temperature grade map
+                if temperature_grades {
+                    let grades = part_number::PartNumber::parse(&mcu.ref_name)
+                        .map(|part| part.temperature_grades(range))
+                        .unwrap_or_default();
+                    for (ordering_code, max) in grades {
+                        maps.temperature_grades
+                            .insert(ordering_code, (mcu.ref_name.clone(), max));
+                    }
+                }
+            }
+
+            // Fill die map
+            if let Some(die) = mcu_dat.get_die() {
+                dependencies.push(die_to_feature(die));
+                maps.dies
+                    .entry(die.to_string())
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            // Fill frequency map
+            if let Some(frequency) = mcu_dat.get_max_frequency() {
+                dependencies.push(frequency_to_feature(frequency));
+                maps.frequencies
+                    .entry(frequency)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            // Fill voltage map
+            if let Some(range) = mcu_dat.get_voltage_range() {
+                dependencies.push(voltage_range_to_feature(range));
+                maps.voltages
+                    .entry(range)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            // Fill peripheral map
+            for instance in peripheral_instances(&mcu_dat) {
+                dependencies.push(peripheral_instance_to_feature(instance));
+                maps.peripherals
+                    .entry(instance.to_string())
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            // Fill IP version map
+            for feature in ip_version_features(&mcu_dat, &ip_names) {
+                dependencies.push(feature.clone());
+                maps.ip_versions
+                    .entry(feature)
+                    .or_insert(vec![])
+                    .push(mcu.ref_name.clone());
+            }
+
+            maps.aliases.insert(mcu.ref_name.clone(), dependencies);
+            maps.mcus.insert(mcu.ref_name.clone(), (mcu, mcu_dat));
         }
     }
 
     match generate {
-        GenerateTarget::Features => generate_features(
-            &mcu_map,
-            &mcu_gpio_map,
-            &mcu_package_map,
-            &mcu_eeprom_size_map,
-            &mcu_flash_size_map,
-            &mcu_ram_size_map,
-            &mcu_family,
+        GenerateTarget::Features => generate_features(&maps)?,
+        GenerateTarget::PinMappings => generate_pin_mappings(
+            &maps.mcus,
+            &maps.gpio,
+            db_dir,
+            serial_traits,
+            reserved_pin_warnings,
         )?,
-        GenerateTarget::PinMappings => generate_pin_mappings(&mcu_gpio_map, &db_dir)?,
-        GenerateTarget::EepromSizes => generate_eeprom_sizes(&mcu_eeprom_size_map)?,
+        GenerateTarget::EepromSizes => generate_eeprom_sizes(&maps.eeprom_sizes)?,
+        GenerateTarget::TemperatureRanges => {
+            generate_temperature_ranges(&maps.temperatures, &maps.temperature_grades)?
+        }
+        GenerateTarget::Cores => generate_cores(&maps.cores)?,
+        GenerateTarget::Limits => generate_limits(&maps.frequencies, &maps.voltages)?,
+        GenerateTarget::DeviceIds => generate_device_ids(&maps.mcus, &maps.dies, mcu_family)?,
+        GenerateTarget::GpioPorts => generate_gpio_ports(&maps.mcus)?,
+        GenerateTarget::PinCounts => generate_pin_counts(&maps.pin_counts, &maps.io_counts)?,
+        GenerateTarget::AdcPins => generate_adc_pins(&maps.mcus, &maps.gpio)?,
+        GenerateTarget::MemoryPins => generate_memory_pins(&maps.gpio, db_dir)?,
+        GenerateTarget::PhyPins => generate_phy_pins(&maps.gpio, db_dir)?,
+        GenerateTarget::ExtiPins => generate_exti_pins(&maps.mcus)?,
+        GenerateTarget::ReservedPins => generate_reserved_pins(&maps.mcus)?,
     };
 
     Ok(())
 }
 
-lazy_static! {
-    static ref FEATURE_DEPENDENCIES: HashMap<&'static str, HashMap<&'static str, &'static str>> = {
-        let mut m = HashMap::new();
-
-        // STM32L0
-        let mut l0 = HashMap::new();
-        l0.insert("^STM32L0.1", "stm32l0x1");
-        l0.insert("^STM32L0.2", "stm32l0x2");
-        l0.insert("^STM32L0.3", "stm32l0x3");
-        m.insert("STM32L0", l0);
-
-        m
-    };
-}
-
 /// Generate all Cargo features
 ///
 /// Feature categories:
 ///
 /// - IO features (`io-*`)
+/// - GPIO port features (`gpio-port*`)
+/// - Line features (e.g. `stm32l0x1`)
+/// - Core features (e.g. `cortex-m0plus`)
 /// - EEPROM features (`eeprom-*`)
+/// - Flash features (`flash-*`)
+/// - RAM features (`ram-*`)
+/// - Package features (e.g. `lqfp32`)
+/// - Pin count features (`pins-*`)
+/// - I/O count features (`io-count-*`)
+/// - Temperature range features (`temp-range-max-*`)
+/// - Die features (`die-*`)
+/// - CPU frequency features (`sysclk-max-*`)
+/// - Supply voltage features (`vdd-*`)
+/// - Peripheral features (`has-*`)
+/// - IP version features (e.g. `usart-*`), only for IPs selected on the
+///   command line
 ///
 /// Finally, the MCU features are printed, they act purely as aliases for the
 /// other features.
-fn generate_features(
-    mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>,
-    mcu_gpio_map: &HashMap<String, Vec<String>>,
-    mcu_package_map: &HashMap<String, String>,
-    mcu_eeprom_size_map: &HashMap<u32, Vec<String>>,
-    mcu_flash_size_map: &HashMap<u32, Vec<String>>,
-    mcu_ram_size_map: &HashMap<u32, Vec<String>>,
-    mcu_family: &str,
-) -> Result<(), String> {
+fn generate_features(maps: &McuMaps) -> Result<(), String> {
     // IO features
-    let mut io_features = mcu_gpio_map
+    let mut io_features = maps
+        .gpio
         .keys()
         .map(|gpio| gpio_version_to_feature(gpio))
         .collect::<Result<Vec<String>, String>>()?;
@@ -237,8 +806,37 @@ fn generate_features(
     }
     println!();
 
+    // GPIO ports
+    let mut gpio_ports = maps.gpio_ports.keys().collect::<Vec<_>>();
+    gpio_ports.sort();
+    println!("# Features based on the available GPIO ports");
+    for port in gpio_ports {
+        println!("{} = []", gpio_port_to_feature(*port));
+    }
+    println!();
+
+    // Lines
+    let mut lines = maps.lines.values().collect::<Vec<_>>();
+    lines.sort_by(|a, b| compare_str(a, b));
+    lines.dedup();
+    println!("# Features based on the MCU line");
+    for feature in lines {
+        println!("{} = []", feature);
+    }
+    println!();
+
+    // Cores
+    let mut cores = maps.cores.keys().map(|(core, _)| core).collect::<Vec<_>>();
+    cores.sort_by(|a, b| compare_str(a, b));
+    cores.dedup();
+    println!("# Features based on the CPU cores");
+    for feature in cores {
+        println!("{} = []", feature);
+    }
+    println!();
+
     // EEPROM sizes
-    let mut eeprom_sizes = mcu_eeprom_size_map.keys().collect::<Vec<_>>();
+    let mut eeprom_sizes = maps.eeprom_sizes.keys().collect::<Vec<_>>();
     eeprom_sizes.sort();
     println!("# Features based on EEPROM size (in bytes)");
     for size in eeprom_sizes {
@@ -247,7 +845,7 @@ fn generate_features(
     println!();
 
     // Flash sizes
-    let mut flash_sizes = mcu_flash_size_map.keys().collect::<Vec<_>>();
+    let mut flash_sizes = maps.flash_sizes.keys().collect::<Vec<_>>();
     flash_sizes.sort();
     println!("# Features based on Flash size (in kbytes)");
     for size in flash_sizes {
@@ -256,7 +854,7 @@ fn generate_features(
     println!();
 
     // RAM sizes
-    let mut ram_sizes = mcu_ram_size_map.keys().collect::<Vec<_>>();
+    let mut ram_sizes = maps.ram_sizes.keys().collect::<Vec<_>>();
     ram_sizes.sort();
     println!("# Features based on RAM size (in kbytes)");
     for size in ram_sizes {
@@ -265,12 +863,9 @@ fn generate_features(
     println!();
 
     // Physical packages
-    if !mcu_package_map.is_empty() {
+    if !maps.packages.is_empty() {
         println!("# Physical packages");
-        let mut packages = mcu_package_map
-            .values()
-            .map(|v| v.to_lowercase())
-            .collect::<Vec<_>>();
+        let mut packages = maps.packages.values().collect::<Vec<_>>();
         packages.sort_by(|a, b| compare_str(a, b));
         packages.dedup();
         for pkg in packages {
@@ -279,62 +874,114 @@ fn generate_features(
         println!();
     }
 
-    // MCU features
-    let mut mcu_aliases = vec![];
-    for (gpio, mcu_list) in mcu_gpio_map {
-        let gpio_version_feature = gpio_version_to_feature(gpio).unwrap();
-        for mcu in mcu_list {
-            let mut dependencies = vec![];
+    // Pin counts
+    let mut pin_counts = maps.pin_counts.keys().collect::<Vec<_>>();
+    pin_counts.sort();
+    println!("# Features based on the pin count of the package");
+    for count in pin_counts {
+        println!("{} = []", pin_count_to_feature(*count));
+    }
+    println!();
 
-            // Static feature dependencies
-            if let Some(family) = FEATURE_DEPENDENCIES.get(mcu_family) {
-                for (pattern, feature) in family {
-                    if Regex::new(pattern).unwrap().is_match(&mcu) {
-                        dependencies.push(feature.to_string());
-                        break;
-                    }
-                }
-            }
+    // I/O counts
+    let mut io_counts = maps.io_counts.keys().collect::<Vec<_>>();
+    io_counts.sort();
+    println!("# Features based on the number of I/Os");
+    for count in io_counts {
+        println!("{} = []", io_count_to_feature(*count));
+    }
+    println!();
 
-            // Package based feature
-            if let Some(package) = mcu_package_map.get(mcu) {
-                dependencies.push(package.to_lowercase());
-            }
+    // Temperatures
+    let mut temperatures = maps.temperatures.keys().collect::<Vec<_>>();
+    temperatures.sort();
+    println!("# Features based on the maximum ambient temperature of all orderable grades (in °C)");
+    for max in temperatures {
+        println!("{} = []", temperature_range_to_feature(*max));
+    }
+    println!();
 
-            // GPIO version feature
-            dependencies.push(gpio_version_feature.clone());
+    // Temperature grades
+    if !maps.temperature_grades.is_empty() {
+        let mut grades = maps
+            .temperature_grades
+            .values()
+            .map(|(_, max)| *max)
+            .collect::<Vec<_>>();
+        grades.sort();
+        grades.dedup();
+        println!("# Features based on the temperature grade of the ordering code (in °C)");
+        for max in grades {
+            println!("{} = []", temperature_grade_to_feature(max));
+        }
+        println!();
+    }
 
-            let (mcu_info, mcu_dat) = mcu_map.get(mcu).unwrap();
+    // Dies
+    let mut dies = maps.dies.keys().collect::<Vec<_>>();
+    dies.sort();
+    println!("# Features based on the die (DBGMCU_IDCODE DEV_ID)");
+    for die in dies {
+        println!("{} = []", die_to_feature(die));
+    }
+    println!();
 
-            // EEPROM size
-            if let Some(size) = mcu_dat.get_eeprom_size() {
-                dependencies.push(eeprom_size_to_feature(size));
-            }
+    // CPU frequencies
+    let mut frequencies = maps.frequencies.keys().collect::<Vec<_>>();
+    frequencies.sort();
+    println!("# Features based on the maximum CPU frequency (in MHz)");
+    for frequency in frequencies {
+        println!("{} = []", frequency_to_feature(*frequency));
+    }
+    println!();
 
-            // Flash & RAM size
-            if let Some(flash_size) = mcu_info.flash_size() {
-                dependencies.push(flash_size_to_feature(flash_size));
-            }
+    // Supply voltages
+    let mut voltages = maps.voltages.keys().collect::<Vec<_>>();
+    voltages.sort();
+    println!("# Features based on the supply voltage range (in mV)");
+    for range in voltages {
+        println!("{} = []", voltage_range_to_feature(*range));
+    }
+    println!();
 
-            if let Some(ram_size) = mcu_info.ram_size() {
-                dependencies.push(ram_size_to_feature(ram_size));
-            }
+    // Peripheral instances
+    let mut peripherals = maps.peripherals.keys().collect::<Vec<_>>();
+    peripherals.sort_by(|a, b| compare_str(a, b));
+    println!("# Features based on the available peripheral instances");
+    for instance in peripherals {
+        println!("{} = []", peripheral_instance_to_feature(instance));
+    }
+    println!();
 
-            mcu_aliases.push(format!(
-                "mcu-{} = [{}]",
-                mcu,
-                &dependencies.iter().map(|val| format!("\"{}\"", val)).fold(
-                    String::new(),
-                    |mut acc, x| {
-                        if !acc.is_empty() {
-                            acc.push_str(", ");
-                        }
-                        acc.push_str(&x);
-                        acc
-                    }
-                )
-            ));
+    // IP versions
+    if !maps.ip_versions.is_empty() {
+        let mut ip_versions = maps.ip_versions.keys().collect::<Vec<_>>();
+        ip_versions.sort();
+        println!("# Features based on the peripheral IP versions");
+        println!("# This determines the register level variant of the peripheral");
+        for feature in ip_versions {
+            println!("{} = []", feature);
         }
+        println!();
+    }
+
+    // MCU features
+    let mut mcu_aliases = vec![];
+    for (mcu, dependencies) in &maps.aliases {
+        mcu_aliases.push(format!(
+            "mcu-{} = [{}]",
+            mcu,
+            &dependencies.iter().map(|val| format!("\"{}\"", val)).fold(
+                String::new(),
+                |mut acc, x| {
+                    if !acc.is_empty() {
+                        acc.push_str(", ");
+                    }
+                    acc.push_str(&x);
+                    acc
+                }
+            )
+        ));
     }
     mcu_aliases.sort();
     println!("# MCU aliases");
@@ -347,22 +994,423 @@ fn generate_features(
         println!("{}", alias);
     }
 
+    // Temperature grade aliases
+    if !maps.temperature_grades.is_empty() {
+        let mut grade_aliases = maps
+            .temperature_grades
+            .iter()
+            .map(|(ordering_code, (ref_name, max))| {
+                temperature_grade_alias(ordering_code, ref_name, *max)
+            })
+            .collect::<Vec<_>>();
+        grade_aliases.sort();
+        println!();
+        println!("# MCU aliases of ordering codes with a temperature grade");
+        for alias in grade_aliases {
+            println!("{}", alias);
+        }
+    }
+
     Ok(())
 }
 
 /// Generate the pin mappings for the target MCU family.
 fn generate_pin_mappings(
+    mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>,
     mcu_gpio_map: &HashMap<String, Vec<String>>,
     db_dir: &Path,
+    serial_traits: SerialPinTraits,
+    reserved_pin_warnings: bool,
 ) -> Result<(), String> {
     let mut gpio_versions = mcu_gpio_map.keys().collect::<Vec<_>>();
     gpio_versions.sort();
     for gpio in gpio_versions {
-        let gpio_version_feature = gpio_version_to_feature(&gpio)?;
+        let gpio_version_feature = gpio_version_to_feature(gpio)?;
         println!("#[cfg(feature = \"{}\")]", gpio_version_feature);
-        let gpio_data = internal_peripheral::IpGPIO::load(db_dir, &gpio)
+        let gpio_data = internal_peripheral::IpGPIO::load(db_dir, gpio)
             .map_err(|e| format!("Could not load IP GPIO file: {}", e))?;
-        render_pin_modes(&gpio_data);
+        let reserved_pins = if reserved_pin_warnings {
+            mcu_reserved_pins(mcu_map, &mcu_gpio_map[gpio])
+        } else {
+            HashMap::new()
+        };
+        render_pin_modes(&gpio_data, serial_traits, &reserved_pins);
+        render_tsc_pins(&gpio_version_feature, &gpio_data);
+        render_analog_pins(
+            &gpio_version_feature,
+            &gpio_data,
+            &mcu_pin_signals(mcu_map, &mcu_gpio_map[gpio]),
+        );
+        println!("\n");
+    }
+    Ok(())
+}
+
+/// Memory interface pin tables and the peripherals of each.
+const MEMORY_PIN_PERIPHERALS: &[(&str, &[&str])] = &[
+    ("sdmmc_pins", &["SDIO", "SDMMC"]),
+    ("quadspi_pins", &["QUADSPI"]),
+    ("octospi_pins", &["OCTOSPI", "OCTOSPIM"]),
+    ("fmc_pins", &["FMC", "FSMC"]),
+];
+
+/// Generate the memory interface (SDMMC, QUADSPI, OCTOSPI and FMC) pin mappings.
+///
+/// Every signal of these peripherals gets its own trait, named after the
+/// function of the signal (e.g. `AF10: Bk1Io0Pin<QUADSPI>` for
+/// "QUADSPI_BK1_IO0"). Tables without any pins are skipped.
+fn generate_memory_pins(
+    mcu_gpio_map: &HashMap<String, Vec<String>>,
+    db_dir: &Path,
+) -> Result<(), String> {
+    let mut gpio_versions = mcu_gpio_map.keys().collect::<Vec<_>>();
+    gpio_versions.sort();
+    for gpio in gpio_versions {
+        let gpio_version_feature = gpio_version_to_feature(gpio)?;
+        let gpio_data = internal_peripheral::IpGPIO::load(db_dir, gpio)
+            .map_err(|e| format!("Could not load IP GPIO file: {}", e))?;
+        for (macro_name, peripherals) in MEMORY_PIN_PERIPHERALS {
+            let pin_map = gpio_pin_map(&gpio_data, |p, afio_remap| {
+                p.get_function_modes(afio_remap, |signal| {
+                    peripherals.contains(&signal.peripheral.as_str())
+                })
+            });
+            if pin_map.values().all(|modes| modes.is_empty()) {
+                continue;
+            }
+            println!("#[cfg(feature = \"{}\")]", gpio_version_feature);
+            render_pin_map(macro_name, pin_map);
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// PHY interface pin tables, with the peripheral and the functions required
+/// for a complete pin set.
+const PHY_PIN_GROUPS: &[(&str, &str, &[&str])] = &[
+    (
+        "rmii_pins",
+        "ETH",
+        &[
+            "REF_CLK", "MDIO", "MDC", "CRS_DV", "RXD0", "RXD1", "TX_EN", "TXD0", "TXD1",
+        ],
+    ),
+    (
+        "mii_pins",
+        "ETH",
+        &[
+            "MDIO", "MDC", "TX_CLK", "RX_CLK", "RX_DV", "RXD0", "RXD1", "RXD2", "RXD3", "TX_EN",
+            "TXD0", "TXD1", "TXD2", "TXD3", "CRS", "COL",
+        ],
+    ),
+    (
+        "ulpi_pins",
+        "USB_OTG_HS",
+        &[
+            "ULPI_CK", "ULPI_DIR", "ULPI_NXT", "ULPI_STP", "ULPI_D0", "ULPI_D1", "ULPI_D2",
+            "ULPI_D3", "ULPI_D4", "ULPI_D5", "ULPI_D6", "ULPI_D7",
+        ],
+    ),
+];
+
+/// Return the `functions` of `peripheral` that none of the given signals
+/// provide.
+fn missing_phy_functions<'a>(
+    peripheral: &str,
+    functions: &[&'a str],
+    signals: &[Signal],
+) -> Vec<&'a str> {
+    let routable = signals
+        .iter()
+        .filter(|signal| signal.peripheral == peripheral)
+        .map(|signal| signal.function.as_str())
+        .collect::<HashSet<_>>();
+    functions
+        .iter()
+        .filter(|function| !routable.contains(**function))
+        .cloned()
+        .collect()
+}
+
+/// Generate the Ethernet (RMII and MII) and USB OTG HS ULPI pin mappings.
+///
+/// Every table only contains the signals of its interface, so a HAL can
+/// require a complete pin set. Tables are only generated if every required
+/// signal is routable to at least one pin. A warning is printed for GPIO
+/// versions where only some of them are.
+fn generate_phy_pins(
+    mcu_gpio_map: &HashMap<String, Vec<String>>,
+    db_dir: &Path,
+) -> Result<(), String> {
+    let mut gpio_versions = mcu_gpio_map.keys().collect::<Vec<_>>();
+    gpio_versions.sort();
+    for gpio in gpio_versions {
+        let gpio_version_feature = gpio_version_to_feature(gpio)?;
+        let gpio_data = internal_peripheral::IpGPIO::load(db_dir, gpio)
+            .map_err(|e| format!("Could not load IP GPIO file: {}", e))?;
+        for (macro_name, peripheral, functions) in PHY_PIN_GROUPS {
+            let is_required = |signal: &Signal| {
+                signal.peripheral == *peripheral && functions.contains(&signal.function.as_str())
+            };
+            let signals = gpio_data
+                .gpio_pin
+                .iter()
+                .flat_map(|p| p.get_signals())
+                .collect::<Vec<_>>();
+            let missing = missing_phy_functions(peripheral, functions, &signals);
+            if missing.len() == functions.len() {
+                continue;
+            }
+            if !missing.is_empty() {
+                eprintln!(
+                    "Warning: {}: Incomplete {} pin set, missing {:?}",
+                    gpio, macro_name, missing
+                );
+                continue;
+            }
+            println!("#[cfg(feature = \"{}\")]", gpio_version_feature);
+            render_pin_map(
+                macro_name,
+                gpio_pin_map(&gpio_data, |p, afio_remap| {
+                    p.get_function_modes(afio_remap, is_required)
+                }),
+            );
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// Collect the reserved pins of all given MCUs.
+///
+/// The keys of the returned map are pin names (e.g. "PA13" or "BOOT0" for
+/// dedicated pins), the values are the functions the pin is reserved for (e.g.
+/// "SWDIO").
+fn mcu_reserved_pins(
+    mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>,
+    mcus: &[String],
+) -> HashMap<String, Vec<String>> {
+    let mut reserved_pins: HashMap<String, Vec<String>> = HashMap::new();
+    for mcu in mcus {
+        for pin in mcu_map[mcu].1.get_pins() {
+            let functions = pin.get_reserved_functions();
+            if functions.is_empty() {
+                continue;
+            }
+            let name = pin
+                .get_gpio_name()
+                .unwrap_or_else(|| pin.get_name().to_string());
+            let reserved = reserved_pins.entry(name).or_insert(vec![]);
+            for function in functions {
+                if !reserved.contains(&function) {
+                    reserved.push(function);
+                }
+            }
+        }
+    }
+    reserved_pins
+}
+
+/// Generate a table of the pins of every MCU that are reserved for debugging,
+/// oscillators or boot mode selection.
+fn generate_reserved_pins(
+    mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>,
+) -> Result<(), String> {
+    let mut mcus = mcu_map.keys().collect::<Vec<_>>();
+    mcus.sort_by(|a, b| compare_str(a, b));
+    println!("// Reserved pins per MCU as (pin, functions), generated with cube-parse");
+    println!("const RESERVED_PINS: &[(&str, &[(&str, &[&str])])] = &[");
+    for mcu in mcus {
+        let mut pins = mcu_reserved_pins(mcu_map, &[mcu.to_string()])
+            .into_iter()
+            .collect::<Vec<_>>();
+        pins.sort_by(|a, b| compare_str(&a.0, &b.0));
+        println!("    (\"{}\", &[", mcu);
+        for (name, functions) in pins {
+            let functions = functions
+                .iter()
+                .map(|function| format!("\"{}\"", function))
+                .collect::<Vec<_>>();
+            println!("        (\"{}\", &[{}]),", name, functions.join(", "));
+        }
+        println!("    ]),");
+    }
+    println!("];");
+    Ok(())
+}
+
+/// Collect the signals of the GPIO pins of all given MCUs.
+///
+/// The keys of the returned map are pin names (e.g. "PA0"), the values are
+/// signals parsed from the MCU XML files. Unlike the GPIO IP file, these also
+/// include signals without an AF (e.g. analog signals). The analog-only pads
+/// of dual-pad pins (e.g. "PA0_C") are separate entries.
+fn mcu_pin_signals(
+    mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>,
+    mcus: &[String],
+) -> HashMap<String, Vec<Signal>> {
+    let mut pin_signals: HashMap<String, Vec<Signal>> = HashMap::new();
+    for mcu in mcus {
+        for pin in mcu_map[mcu].1.get_pins() {
+            let name = pin
+                .get_gpio_name()
+                .or_else(|| pin.get_dual_pad_name().map(str::to_string));
+            if let Some(name) = name {
+                let signals = pin_signals.entry(name).or_insert(vec![]);
+                for signal in pin.get_signals().filter_map(Signal::parse) {
+                    if !signals.contains(&signal) {
+                        signals.push(signal);
+                    }
+                }
+            }
+        }
+    }
+    pin_signals
+}
+
+/// Analog peripherals and the analog pin functions of each that are part of
+/// the analog pin mappings.
+const ANALOG_PIN_FUNCTIONS: &[(&str, &str, &[&str])] = &[
+    ("dac_pins", "DAC", &["OUT"]),
+    ("comp_pins", "COMP", &["INP", "INM"]),
+    ("opamp_pins", "OPAMP", &["VINP", "VINM", "VOUT"]),
+];
+
+/// Return the analog pin mapping entries of `peripheral` for a pin, given its
+/// signals.
+///
+/// DAC outputs are returned with their channel number (e.g. `DAC1: 1` for
+/// "DAC1_OUT1"), comparator and opamp pins with the trait of their role (e.g.
+/// `InpPin<COMP1>`). Only signals with one of the given `functions` are
+/// considered.
+fn analog_pin_entries(peripheral: &str, functions: &[&str], signals: &[Signal]) -> Vec<String> {
+    signals
+        .iter()
+        .filter(|signal| signal.peripheral == peripheral)
+        .filter_map(|signal| {
+            let function = signal
+                .function
+                .trim_end_matches(|c: char| c.is_ascii_digit());
+            if !functions.contains(&function) {
+                None
+            } else if peripheral == "DAC" {
+                signal
+                    .index
+                    .map(|channel| format!("{}: {}", signal.instance_name(), channel))
+            } else {
+                Some(format!(
+                    "{}<{}>",
+                    function_to_trait(function),
+                    signal.instance_name()
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Render the DAC, COMP and OPAMP pin mappings.
+///
+/// Analog pins come from the MCU XML files, see `analog_pin_entries`.
+/// Comparator outputs are digital alternate functions, so they are taken from
+/// the GPIO IP file along with their AF (e.g. `AF7: OutPin<COMP1>`) and
+/// rendered in a separate `comp_out_pins` table. Every table is gated by the
+/// GPIO version feature, tables without any pins are skipped.
+fn render_analog_pins(
+    gpio_version_feature: &str,
+    ip: &internal_peripheral::IpGPIO,
+    pin_signals: &HashMap<String, Vec<Signal>>,
+) {
+    for (macro_name, peripheral, functions) in ANALOG_PIN_FUNCTIONS {
+        let pin_map = pin_signals
+            .iter()
+            .map(|(pin, signals)| {
+                (
+                    pin.clone(),
+                    analog_pin_entries(peripheral, functions, signals),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        render_gated_pin_map(gpio_version_feature, macro_name, pin_map);
+        if *peripheral == "COMP" {
+            let outputs = gpio_pin_map(ip, |p, afio_remap| {
+                p.get_function_modes(afio_remap, |signal| {
+                    signal.peripheral == "COMP" && signal.function.starts_with("OUT")
+                })
+            });
+            render_gated_pin_map(gpio_version_feature, "comp_out_pins", outputs);
+        }
+    }
+}
+
+/// Render a pin mapping gated by the GPIO version feature, unless it has no
+/// pins.
+fn render_gated_pin_map(
+    gpio_version_feature: &str,
+    macro_name: &str,
+    pin_map: HashMap<String, Vec<String>>,
+) {
+    if pin_map.values().all(|entries| entries.is_empty()) {
+        return;
+    }
+    println!();
+    println!("#[cfg(feature = \"{}\")]", gpio_version_feature);
+    render_pin_map(macro_name, pin_map);
+}
+
+/// Return the ADC channels of a pin (e.g. `ADC1: 5`), given its signals.
+///
+/// Only analog inputs ("IN5" or "INP5") are considered, negative inputs of
+/// differential channels (e.g. "ADC1_INN5") are skipped. Signals of ADCs
+/// shared by several instances are named by ST using all instance numbers
+/// (e.g. "ADC12_IN5"), these are split into one entry per instance.
+fn adc_channels(signals: &[Signal]) -> Vec<String> {
+    let mut channels = vec![];
+    for signal in signals {
+        if signal.peripheral != "ADC"
+            || !signal.function.starts_with("IN")
+            || signal.function.starts_with("INN")
+        {
+            continue;
+        }
+        let channel = match signal.index {
+            Some(channel) => channel,
+            None => continue,
+        };
+        match signal.instance {
+            Some(instance) => {
+                for digit in instance.to_string().chars() {
+                    channels.push(format!("ADC{}: {}", digit, channel));
+                }
+            }
+            None => channels.push(format!("ADC: {}", channel)),
+        }
+    }
+    channels
+}
+
+/// Generate the ADC pin mappings for the target MCU family.
+///
+/// The mappings contain the ADC instance and channel number of every analog
+/// input (e.g. `PA5 => {ADC1: 5}`), see `adc_channels`. Tables without any
+/// pins are skipped.
+fn generate_adc_pins(
+    mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>,
+    mcu_gpio_map: &HashMap<String, Vec<String>>,
+) -> Result<(), String> {
+    let mut gpio_versions = mcu_gpio_map.keys().collect::<Vec<_>>();
+    gpio_versions.sort();
+    for gpio in gpio_versions {
+        let gpio_version_feature = gpio_version_to_feature(gpio)?;
+        let pin_map = mcu_pin_signals(mcu_map, &mcu_gpio_map[gpio])
+            .into_iter()
+            .map(|(pin, signals)| (pin, adc_channels(&signals)))
+            .collect::<HashMap<_, _>>();
+        if pin_map.values().all(|channels| channels.is_empty()) {
+            continue;
+        }
+        println!("#[cfg(feature = \"{}\")]", gpio_version_feature);
+        render_pin_map("adc_pins", pin_map);
         println!("\n");
     }
     Ok(())
@@ -378,32 +1426,326 @@ fn generate_eeprom_sizes(mcu_eeprom_size_map: &HashMap<u32, Vec<String>>) -> Res
     Ok(())
 }
 
-fn render_pin_modes(ip: &internal_peripheral::IpGPIO) {
+/// Generate code containing the maximum ambient temperature of all orderable
+/// grades.
+///
+/// If temperature grades are enabled, the maximum temperature of the selected
+/// grade is generated as well.
+fn generate_temperature_ranges(
+    mcu_temperature_map: &HashMap<i32, Vec<String>>,
+    mcu_temperature_grade_map: &HashMap<String, (String, i32)>,
+) -> Result<(), String> {
+    let mut temperatures = mcu_temperature_map.keys().collect::<Vec<_>>();
+    temperatures.sort();
+    println!(
+        "// Maximum ambient temperature of all orderable grades in °C, generated with cube-parse"
+    );
+    for max in temperatures {
+        println!(
+            "#[cfg(feature = \"{}\")]",
+            temperature_range_to_feature(*max)
+        );
+        println!("const TEMPERATURE_RANGE_MAX_CELSIUS: i32 = {};", max);
+    }
+
+    let mut grades = mcu_temperature_grade_map
+        .values()
+        .map(|(_, max)| *max)
+        .collect::<Vec<_>>();
+    grades.sort();
+    grades.dedup();
+    if !grades.is_empty() {
+        println!();
+        println!("// Maximum ambient temperature of the grade in °C, generated with cube-parse");
+        for max in grades {
+            println!(
+                "#[cfg(feature = \"{}\")]",
+                temperature_grade_to_feature(max)
+            );
+            println!("const TEMPERATURE_GRADE_MAX_CELSIUS: i32 = {};", max);
+        }
+    }
+    Ok(())
+}
+
+/// Generate code containing the Rust target triples of the CPU cores.
+fn generate_cores(mcu_core_map: &HashMap<(String, &str), Vec<String>>) -> Result<(), String> {
+    let mut cores = mcu_core_map.keys().collect::<Vec<_>>();
+    cores.sort();
+    println!("// Rust target triples of the CPU cores, generated with cube-parse");
+    for (feature, target) in cores {
+        println!("#[cfg(feature = \"{}\")]", feature);
+        println!(
+            "const {}_TARGET: &str = \"{}\";",
+            feature.to_uppercase().replace('-', "_"),
+            target
+        );
+    }
+    Ok(())
+}
+
+/// Generate code containing the maximum CPU frequency and the supply voltage
+/// range.
+fn generate_limits(
+    mcu_frequency_map: &HashMap<u32, Vec<String>>,
+    mcu_voltage_map: &HashMap<(u32, u32), Vec<String>>,
+) -> Result<(), String> {
+    let mut frequencies = mcu_frequency_map.keys().collect::<Vec<_>>();
+    frequencies.sort();
+    println!("// Maximum CPU frequency in Hz, generated with cube-parse");
+    for frequency in frequencies {
+        println!("#[cfg(feature = \"{}\")]", frequency_to_feature(*frequency));
+        println!("const MAX_SYSCLK_HZ: u32 = {}_000_000;", frequency);
+    }
+    println!();
+
+    let mut voltages = mcu_voltage_map.keys().collect::<Vec<_>>();
+    voltages.sort();
+    println!("// Supply voltage range in mV, generated with cube-parse");
+    for range in voltages {
+        let feature = voltage_range_to_feature(*range);
+        println!("#[cfg(feature = \"{}\")]", feature);
+        println!("const VDD_MIN_MV: u32 = {};", range.0);
+        println!("#[cfg(feature = \"{}\")]", feature);
+        println!("const VDD_MAX_MV: u32 = {};", range.1);
+    }
+    Ok(())
+}
+
+/// Generate code containing the DBGMCU_IDCODE DEV_ID of the selected die, and
+/// a lookup table from DEV_ID to family, line and die.
+fn generate_device_ids(
+    mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>,
+    mcu_die_map: &HashMap<String, Vec<String>>,
+    mcu_family: &str,
+) -> Result<(), String> {
+    let mut dies = mcu_die_map.keys().collect::<Vec<_>>();
+    dies.sort();
+    let mut table = vec![];
+    println!("// DBGMCU_IDCODE DEV_ID of the die, generated with cube-parse");
+    for die in dies {
+        let device_id = match die_to_device_id(die) {
+            Some(device_id) => device_id,
+            None => {
+                eprintln!("Warning: Could not parse die {:?}", die);
+                continue;
+            }
+        };
+        println!("#[cfg(feature = \"{}\")]", die_to_feature(die));
+        println!("const DEV_ID: u16 = {:#05x};", device_id);
+
+        let mut lines = mcu_die_map[die]
+            .iter()
+            .filter_map(|mcu| mcu_map[mcu].1.get_line())
+            .collect::<Vec<_>>();
+        lines.sort_by(|a, b| compare_str(a, b));
+        lines.dedup();
+        for line in lines {
+            table.push(format!(
+                "    ({:#05x}, \"{}\", \"{}\", \"{}\"),",
+                device_id, mcu_family, line, die
+            ));
+        }
+    }
+    println!();
+
+    println!("// DEV_ID lookup table (DEV_ID, family, line, die), generated with cube-parse");
+    println!("const DEVICE_IDS: &[(u16, &str, &str, &str)] = &[");
+    for entry in table {
+        println!("{}", entry);
+    }
+    println!("];");
+    Ok(())
+}
+
+/// Generate a table containing the available GPIO pins of every MCU.
+fn generate_gpio_ports(mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>) -> Result<(), String> {
+    let mut mcus = mcu_map.keys().collect::<Vec<_>>();
+    mcus.sort_by(|a, b| compare_str(a, b));
+    println!("// Available GPIO pins per port as bit mask, generated with cube-parse");
+    println!("const GPIO_PORTS: &[(&str, &[(char, u16)])] = &[");
+    for mcu in mcus {
+        let ports = mcu_map[mcu]
+            .1
+            .get_gpio_ports()
+            .iter()
+            .map(|(port, mask)| format!("('{}', {:#06x})", port, mask))
+            .collect::<Vec<_>>();
+        println!("    (\"{}\", &[{}]),", mcu, ports.join(", "));
+    }
+    println!("];");
+    Ok(())
+}
+
+/// Return the wakeup (e.g. "WKUP1") or tamper (e.g. "TAMP2") function of a
+/// signal, if it has one.
+///
+/// Tamper inputs of the TAMP peripheral (e.g. "TAMP_IN2") are named like the
+/// ones of the RTC (e.g. "RTC_TAMP2").
+fn wakeup_or_tamper_function(signal: &Signal) -> Option<String> {
+    match signal.peripheral.as_str() {
+        "SYS" | "PWR" if signal.function.starts_with("WKUP") => Some(signal.function.clone()),
+        "RTC" if signal.function.starts_with("TAMP") => Some(signal.function.clone()),
+        "TAMP" if signal.function.starts_with("IN") => {
+            signal.index.map(|index| format!("TAMP{}", index))
+        }
+        _ => None,
+    }
+}
+
+/// Generate a table of the GPIO pins of every MCU, with their EXTI line and
+/// their wakeup (e.g. "WKUP1") and RTC tamper (e.g. "TAMP2") functions.
+fn generate_exti_pins(mcu_map: &HashMap<String, (&family::Mcu, mcu::Mcu)>) -> Result<(), String> {
+    let mut mcus = mcu_map.keys().collect::<Vec<_>>();
+    mcus.sort_by(|a, b| compare_str(a, b));
+    println!("// GPIO pins per MCU as (pin, EXTI line, wakeup and tamper functions),");
+    println!("// generated with cube-parse");
+    println!("const EXTI_PINS: &[(&str, &[(&str, u8, &[&str])])] = &[");
+    for mcu in mcus {
+        let mut pins = mcu_map[mcu]
+            .1
+            .get_pins()
+            .filter_map(|pin| {
+                let (_, line) = pin.get_gpio()?;
+                let mut functions = pin
+                    .get_signals()
+                    .filter_map(Signal::parse)
+                    .filter_map(|signal| wakeup_or_tamper_function(&signal))
+                    .map(|function| format!("\"{}\"", function))
+                    .collect::<Vec<_>>();
+                functions.sort_by(|a, b| compare_str(a, b));
+                functions.dedup();
+                Some((pin.get_gpio_name()?, line, functions))
+            })
+            .collect::<Vec<_>>();
+        pins.sort_by(|a, b| compare_str(&a.0, &b.0));
+        pins.dedup_by(|a, b| a.0 == b.0);
+        println!("    (\"{}\", &[", mcu);
+        for (name, line, functions) in pins {
+            println!(
+                "        (\"{}\", {}, &[{}]),",
+                name,
+                line,
+                functions.join(", ")
+            );
+        }
+        println!("    ]),");
+    }
+    println!("];");
+    Ok(())
+}
+
+/// Generate code containing the pin count of the package and the number of
+/// I/Os.
+fn generate_pin_counts(
+    mcu_pin_count_map: &HashMap<u32, Vec<String>>,
+    mcu_io_count_map: &HashMap<u32, Vec<String>>,
+) -> Result<(), String> {
+    let mut pin_counts = mcu_pin_count_map.keys().collect::<Vec<_>>();
+    pin_counts.sort();
+    println!("// Pin count of the package, generated with cube-parse");
+    for count in pin_counts {
+        println!("#[cfg(feature = \"{}\")]", pin_count_to_feature(*count));
+        println!("const PIN_COUNT: u32 = {};", count);
+    }
+    println!();
+
+    let mut io_counts = mcu_io_count_map.keys().collect::<Vec<_>>();
+    io_counts.sort();
+    println!("// Number of I/Os, generated with cube-parse");
+    for count in io_counts {
+        println!("#[cfg(feature = \"{}\")]", io_count_to_feature(*count));
+        println!("const IO_COUNT: u32 = {};", count);
+    }
+    Ok(())
+}
+
+fn render_pin_modes(
+    ip: &internal_peripheral::IpGPIO,
+    serial_traits: SerialPinTraits,
+    reserved_pins: &HashMap<String, Vec<String>>,
+) {
+    render_annotated_pin_map(
+        "pins",
+        gpio_pin_map(ip, |p, afio_remap| {
+            p.get_af_modes(afio_remap, serial_traits)
+        }),
+        reserved_pins,
+    );
+}
+
+/// Render the TSC pin mappings, gated by the GPIO version feature.
+///
+/// The table is skipped if no pin has a TSC function.
+fn render_tsc_pins(gpio_version_feature: &str, ip: &internal_peripheral::IpGPIO) {
+    let pin_map = gpio_pin_map(ip, internal_peripheral::GPIOPin::get_tsc_modes);
+    if pin_map.values().all(|modes| modes.is_empty()) {
+        return;
+    }
+    println!();
+    println!("#[cfg(feature = \"{}\")]", gpio_version_feature);
+    render_pin_map("tsc_pins", pin_map);
+}
+
+/// Map the name of every pin in the GPIO IP file to the AF modes returned by
+/// `modes`, which is passed whether the file uses AFIO remap options (see
+/// `IpGPIO::afio_remap`).
+fn gpio_pin_map(
+    ip: &internal_peripheral::IpGPIO,
+    modes: impl Fn(&internal_peripheral::GPIOPin, bool) -> Vec<String>,
+) -> HashMap<String, Vec<String>> {
     let mut pin_map: HashMap<String, Vec<String>> = HashMap::new();
 
     for p in &ip.gpio_pin {
         let name = p.get_name();
         if let Some(n) = name {
-            pin_map.insert(n, p.get_af_modes());
+            pin_map.insert(n, modes(p, ip.afio_remap));
         }
     }
 
+    pin_map
+}
+
+/// Render a pin map (pin name -> entries) as a macro invocation, e.g.
+/// `pins! { PA0 => {AF4: TxPin<USART2>}, }`.
+fn render_pin_map(macro_name: &str, pin_map: HashMap<String, Vec<String>>) {
+    render_annotated_pin_map(macro_name, pin_map, &HashMap::new());
+}
+
+/// Render a pin map like `render_pin_map`, with a warning comment in front of
+/// every pin that is reserved for the functions in `reserved_pins`.
+fn render_annotated_pin_map(
+    macro_name: &str,
+    pin_map: HashMap<String, Vec<String>>,
+    reserved_pins: &HashMap<String, Vec<String>>,
+) {
     let mut pin_map = pin_map
         .into_iter()
         .map(|(k, mut v)| {
             #[allow(clippy::redundant_closure)]
             v.sort_by(|a, b| compare_str(a, b));
+            v.dedup();
             (k, v)
         })
         .collect::<Vec<_>>();
 
-    pin_map.sort_by(|a, b| compare_str(&a.0, &b.0));
+    // Dual-pad pins (e.g. "PA0_C") compare equal to their GPIO twin, so fall
+    // back to plain string order for those
+    pin_map.sort_by(|a, b| compare_str(&a.0, &b.0).then_with(|| a.0.cmp(&b.0)));
 
-    println!("pins! {{");
+    println!("{}! {{", macro_name);
     for (n, af) in pin_map {
         if af.is_empty() {
             continue;
-        } else if af.len() == 1 {
+        }
+        if let Some(functions) = reserved_pins.get(&n) {
+            println!(
+                "    // Warning: {} is reserved for {}",
+                n,
+                functions.join(", ")
+            );
+        }
+        if af.len() == 1 {
             println!("    {} => {{{}}},", n, af[0]);
         } else {
             println!("    {} => {{", n);
@@ -420,6 +1762,14 @@ fn render_pin_modes(ip: &internal_peripheral::IpGPIO) {
 mod tests {
     use super::*;
 
+    /// Parse a list of signal names (e.g. "USART2_TX").
+    fn signals(names: &[&str]) -> Vec<Signal> {
+        names
+            .iter()
+            .map(|name| Signal::parse(name).unwrap())
+            .collect()
+    }
+
     #[test]
     fn test_gpio_version_to_feature() {
         // Success
@@ -441,4 +1791,207 @@ mod tests {
         // Error parsing, too many underscores
         assert!(gpio_version_to_feature("STM32_STM32F333_gpio_v1_0").is_err());
     }
+
+    #[test]
+    fn test_temperature_grade_alias() {
+        assert_eq!(temperature_grade_to_feature(125), "temp-grade-125");
+        assert_eq!(
+            temperature_grade_alias("STM32L071KBT3", "STM32L071KBTx", 125),
+            r#"mcu-STM32L071KBT3 = ["mcu-STM32L071KBTx", "temp-grade-125"]"#
+        );
+    }
+
+    #[test]
+    fn test_wakeup_or_tamper_function() {
+        let function = |name| wakeup_or_tamper_function(&Signal::parse(name).unwrap());
+        assert_eq!(function("SYS_WKUP1"), Some("WKUP1".to_string()));
+        assert_eq!(function("PWR_WKUP4"), Some("WKUP4".to_string()));
+        assert_eq!(function("RTC_TAMP2"), Some("TAMP2".to_string()));
+        assert_eq!(function("TAMP_IN3"), Some("TAMP3".to_string()));
+        assert_eq!(function("TAMP_OUT3"), None);
+        assert_eq!(function("RTC_OUT_ALARM"), None);
+        assert_eq!(function("SYS_SWCLK"), None);
+    }
+
+    #[test]
+    fn test_missing_phy_functions() {
+        let (_, peripheral, functions) = PHY_PIN_GROUPS[0];
+        let rmii = signals(&[
+            "ETH_REF_CLK",
+            "ETH_MDIO",
+            "ETH_MDC",
+            "ETH_CRS_DV",
+            "ETH_RXD0",
+            "ETH_RXD1",
+            "ETH_TX_EN",
+            "ETH_TXD0",
+            "ETH_TXD1",
+            "USART2_TX",
+        ]);
+        assert!(missing_phy_functions(peripheral, functions, &rmii).is_empty());
+
+        let partial = signals(&["ETH_MDIO", "ETH_MDC", "ETH_RXD0", "ETH_RXD1", "ETH_TXD0"]);
+        assert_eq!(
+            missing_phy_functions(peripheral, functions, &partial),
+            vec!["REF_CLK", "CRS_DV", "TX_EN", "TXD1"]
+        );
+        assert_eq!(
+            missing_phy_functions(peripheral, functions, &[]),
+            functions.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_analog_pin_entries() {
+        let signals = signals(&[
+            "DAC1_OUT1",
+            "COMP1_INP",
+            "COMP1_OUT",
+            "OPAMP2_VOUT",
+            "ADC1_IN5",
+        ]);
+        assert_eq!(
+            analog_pin_entries("DAC", &["OUT"], &signals),
+            vec!["DAC1: 1"]
+        );
+        assert_eq!(
+            analog_pin_entries("COMP", &["INP", "INM"], &signals),
+            vec!["InpPin<COMP1>"]
+        );
+        assert_eq!(
+            analog_pin_entries("OPAMP", &["VINP", "VINM", "VOUT"], &signals),
+            vec!["VoutPin<OPAMP2>"]
+        );
+    }
+
+    #[test]
+    fn test_adc_channels() {
+        assert_eq!(adc_channels(&signals(&["ADC_IN0"])), vec!["ADC: 0"]);
+        assert_eq!(
+            adc_channels(&signals(&["ADC1_INP5", "ADC1_INN4", "USART2_TX"])),
+            vec!["ADC1: 5"]
+        );
+        assert_eq!(
+            adc_channels(&signals(&["ADC12_IN5"])),
+            vec!["ADC1: 5", "ADC2: 5"]
+        );
+        assert_eq!(
+            adc_channels(&signals(&["ADC123_INP10"])),
+            vec!["ADC1: 10", "ADC2: 10", "ADC3: 10"]
+        );
+        assert!(adc_channels(&signals(&["ADC1_EXTI11", "DAC_OUT1"])).is_empty());
+    }
+
+    #[test]
+    fn test_die_to_device_id() {
+        assert_eq!(die_to_device_id("DIE447"), Some(0x447));
+        assert_eq!(die_to_device_id("DIE41A"), Some(0x41a));
+        assert_eq!(die_to_device_id("447"), None);
+        assert_eq!(die_to_device_id("DIEXYZ"), None);
+        assert_eq!(die_to_feature("DIE41A"), "die-41a");
+    }
+
+    #[test]
+    fn test_core_to_feature() {
+        assert_eq!(
+            core_to_feature("Arm Cortex-M0+", "STM32L0").unwrap(),
+            ("cortex-m0plus".to_string(), "thumbv6m-none-eabi")
+        );
+        assert_eq!(
+            core_to_feature("Arm Cortex-M3", "STM32F1").unwrap(),
+            ("cortex-m3".to_string(), "thumbv7m-none-eabi")
+        );
+        assert_eq!(
+            core_to_feature("Arm Cortex-M4", "STM32F3").unwrap(),
+            ("cortex-m4f".to_string(), "thumbv7em-none-eabihf")
+        );
+        assert_eq!(
+            core_to_feature("Arm Cortex-M4", "STM32WL").unwrap(),
+            ("cortex-m4".to_string(), "thumbv7em-none-eabi")
+        );
+        assert_eq!(
+            core_to_feature("Arm Cortex-M33", "STM32L5").unwrap(),
+            ("cortex-m33f".to_string(), "thumbv8m.main-none-eabihf")
+        );
+        assert_eq!(
+            core_to_feature("Arm Cortex-A7", "STM32MP1").unwrap(),
+            ("cortex-a7".to_string(), "armv7a-none-eabihf")
+        );
+        assert!(core_to_feature("Arm Cortex-R5", "STM32L0").is_none());
+    }
+
+    #[test]
+    fn test_is_peripheral_ip() {
+        assert!(is_peripheral_ip("USART"));
+        assert!(is_peripheral_ip("DMA2D"));
+        assert!(!is_peripheral_ip("RCC"));
+        assert!(!is_peripheral_ip("DMA"));
+        assert!(!is_peripheral_ip("FREERTOS"));
+        assert!(!is_peripheral_ip("CORTEX_M7"));
+        assert!(!is_peripheral_ip("CORTEX_M33_NS"));
+    }
+
+    #[test]
+    fn test_package_to_feature() {
+        assert_eq!(
+            package_to_feature("LQFP48", PackageFeatureStyle::Lowercase),
+            "lqfp48"
+        );
+        assert_eq!(
+            package_to_feature("UFQFPN32", PackageFeatureStyle::Prefixed),
+            "package-ufqfpn32"
+        );
+    }
+
+    #[test]
+    fn test_line_to_feature() {
+        assert_eq!(line_to_feature("STM32L0x1").unwrap(), "stm32l0x1");
+        assert_eq!(
+            line_to_feature("STM32F0x0 Value Line").unwrap(),
+            "stm32f0x0"
+        );
+        assert_eq!(line_to_feature("STM32F4x5/4x7").unwrap(), "stm32f4x5-4x7");
+        assert!(line_to_feature("").is_none());
+    }
+
+    #[test]
+    fn test_parse_line_overrides() {
+        let overrides =
+            parse_line_overrides("# STM32L0\n\n^STM32L0.1 = stm32l0x1\n^STM32L0.2=stm32l0x2\n")
+                .unwrap();
+        assert_eq!(overrides.len(), 2);
+        assert!(overrides[0].0.is_match("STM32L071KBTx"));
+        assert_eq!(overrides[0].1, "stm32l0x1");
+        assert_eq!(overrides[1].1, "stm32l0x2");
+
+        // Error parsing, missing feature
+        assert!(parse_line_overrides("^STM32L0.1 =").is_err());
+
+        // Error parsing, invalid regex
+        assert!(parse_line_overrides("^STM32L0(.1 = stm32l0x1").is_err());
+    }
+
+    #[test]
+    fn test_ip_version_to_feature() {
+        assert_eq!(
+            ip_version_to_feature("RCC", "STM32L051_rcc_v1_0"),
+            "rcc-STM32L051_v1_0"
+        );
+        assert_eq!(
+            ip_version_to_feature("RCC", "STM32L051_rcc_v2_0"),
+            "rcc-STM32L051_v2_0"
+        );
+        assert_eq!(
+            ip_version_to_feature("TIM", "tim1_8_v1_0"),
+            "tim-tim1_8_v1_0"
+        );
+        assert_eq!(
+            ip_version_to_feature("USART", "sci3_v1_1_Cube"),
+            "usart-sci3_v1_1"
+        );
+        assert_eq!(
+            ip_version_to_feature("ADC", "aditf4_v1_1_Cube"),
+            "adc-aditf4_v1_1"
+        );
+    }
 }
@@ -1,16 +1,47 @@
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::path::Path;
 
+use lazy_static::lazy_static;
+use regex::Regex;
 use serde_derive::Deserialize;
 
+use crate::signal::Signal;
 use crate::utils::load_file;
 
+/// SYS functions of the SWD and JTAG debug pins. Combined functions (e.g.
+/// "JTMS-SWDIO" or "JTDO-TRACESWO") are reserved if any of their parts is.
+const RESERVED_SYS_FUNCTIONS: &[&str] = &[
+    "SWDIO", "SWCLK", "JTMS", "JTCK", "JTDI", "JTDO", "JTRST", "NJTRST",
+];
+
+/// RCC functions of the oscillator pins.
+const RESERVED_RCC_FUNCTIONS: &[&str] = &["OSC_IN", "OSC_OUT", "OSC32_IN", "OSC32_OUT"];
+
 #[derive(Debug, Deserialize)]
 pub struct Mcu {
+    #[serde(rename = "Line", default)]
+    line: String,
+    #[serde(rename = "Core", default)]
+    cores: Vec<String>,
+    #[serde(rename = "Frequency", default)]
+    frequencies: Vec<String>,
     #[serde(rename = "IP", default)]
     ip: Vec<IP>,
+    #[serde(rename = "Pin", default)]
+    pins: Vec<Pin>,
     #[serde(rename = "E2prom")]
     eeprom_size_bytes: String,
+    #[serde(rename = "IONb", default)]
+    io_count: String,
+    #[serde(rename = "Die")]
+    die: Option<String>,
+    #[serde(rename = "Flash", default)]
+    flash_sizes: Vec<String>,
+    #[serde(rename = "Voltage")]
+    voltage: Option<Voltage>,
+    #[serde(rename = "Temperature")]
+    temperature: Option<Temperature>,
 }
 
 impl Mcu {
@@ -22,10 +53,200 @@ impl Mcu {
         self.ip.iter().find(|v| v.name == name)
     }
 
+    /// Return an iterator over all IP instances
+    pub fn get_ips(&self) -> impl Iterator<Item = &IP> {
+        self.ip.iter()
+    }
+
+    /// Return an iterator over all pins of the package
+    pub fn get_pins(&self) -> impl Iterator<Item = &Pin> {
+        self.pins.iter()
+    }
+
+    /// Return the available GPIO pins of every port (e.g. 'A') as a bit mask
+    pub fn get_gpio_ports(&self) -> BTreeMap<char, u16> {
+        let mut ports = BTreeMap::new();
+        for (port, number) in self.pins.iter().filter_map(Pin::get_gpio) {
+            *ports.entry(port).or_insert(0) |= 1 << number;
+        }
+        ports
+    }
+
+    /// Return the line (e.g. "STM32L0x1"), if present
+    pub fn get_line(&self) -> Option<&str> {
+        if self.line.is_empty() {
+            None
+        } else {
+            Some(&self.line)
+        }
+    }
+
+    /// Return the CPU cores (e.g. "Arm Cortex-M0+"). Multi-core MCUs have
+    /// more than one core.
+    pub fn get_cores(&self) -> &[String] {
+        &self.cores
+    }
+
+    /// Return the maximum CPU frequency in MHz. For multi-core MCUs, this is
+    /// the frequency of the fastest core.
+    pub fn get_max_frequency(&self) -> Option<u32> {
+        self.frequencies
+            .iter()
+            .filter_map(|frequency| frequency.parse().ok())
+            .max()
+    }
+
+    /// Return the supply voltage range (min, max) in mV
+    pub fn get_voltage_range(&self) -> Option<(u32, u32)> {
+        let voltage = self.voltage.as_ref()?;
+        let min = voltage.min.parse::<f32>().ok()?;
+        let max = voltage.max.parse::<f32>().ok()?;
+        Some(((min * 1000.0).round() as u32, (max * 1000.0).round() as u32))
+    }
+
     /// Return the EEPROM size in bytes
     pub fn get_eeprom_size(&self) -> Option<u32> {
         self.eeprom_size_bytes.parse().ok()
     }
+
+    /// Return the number of I/Os
+    pub fn get_io_count(&self) -> Option<u32> {
+        self.io_count.parse().ok()
+    }
+
+    /// Return the die (e.g. "DIE447")
+    pub fn get_die(&self) -> Option<&str> {
+        self.die.as_deref()
+    }
+
+    /// Return the flash sizes of all variants in kbytes
+    pub fn get_flash_sizes(&self) -> Vec<u32> {
+        self.flash_sizes
+            .iter()
+            .filter_map(|size| size.parse().ok())
+            .collect()
+    }
+
+    /// Return the temperature range (min, max) in °C
+    pub fn get_temperature_range(&self) -> Option<(i32, i32)> {
+        let temperature = self.temperature.as_ref()?;
+        let min = temperature.min.parse::<f32>().ok()?;
+        let max = temperature.max.parse::<f32>().ok()?;
+        Some((min.round() as i32, max.round() as i32))
+    }
+}
+
+lazy_static! {
+    static ref GPIO_PIN: Regex = Regex::new("^P([A-Z])([0-9]{1,2})(?:$|[^0-9_])").unwrap();
+    static ref DUAL_PAD_PIN: Regex = Regex::new("^P[A-Z][0-9]{1,2}_C$").unwrap();
+}
+
+/// Return whether `name` is the name of the analog-only pad of a dual-pad pin
+/// (e.g. "PA0_C").
+pub fn is_dual_pad_name(name: &str) -> bool {
+    DUAL_PAD_PIN.is_match(name)
+}
+
+/// A pin of the package (e.g. "PC14-OSC32_IN" or "VDD").
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Pin {
+    name: String,
+    #[serde(rename = "Type")]
+    pin_type: String,
+    #[serde(rename = "Signal", default)]
+    signals: Vec<PinSignal>,
+}
+
+/// A signal that can be routed to a pin (e.g. "ADC_IN0").
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PinSignal {
+    name: String,
+}
+
+impl Pin {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the name of the GPIO pin (e.g. "PC14" for "PC14-OSC32_IN"), if
+    /// this is a GPIO pin.
+    pub fn get_gpio_name(&self) -> Option<String> {
+        self.get_gpio()
+            .map(|(port, number)| format!("P{}{}", port, number))
+    }
+
+    /// Return the name of the pin if it is the analog-only pad of a dual-pad
+    /// pin (e.g. "PA0_C" on the STM32H7).
+    ///
+    /// These pads are connected to the GPIO pin of the same name through an
+    /// analog switch, but are separate pins of the package.
+    pub fn get_dual_pad_name(&self) -> Option<&str> {
+        if is_dual_pad_name(&self.name) {
+            Some(&self.name)
+        } else {
+            None
+        }
+    }
+
+    /// Return the names of all signals of this pin
+    pub fn get_signals(&self) -> impl Iterator<Item = &str> {
+        self.signals.iter().map(|signal| signal.name.as_str())
+    }
+
+    /// Return the functions this pin is reserved for: SWD and JTAG debug (e.g.
+    /// "JTCK-SWCLK" or "JTDI"), oscillator (e.g. "OSC32_IN") and boot ("BOOT0")
+    /// functions.
+    pub fn get_reserved_functions(&self) -> Vec<String> {
+        let mut functions = self
+            .get_signals()
+            .filter_map(Signal::parse)
+            .filter(|signal| match signal.peripheral.as_str() {
+                "SYS" => signal
+                    .function
+                    .split('-')
+                    .any(|part| RESERVED_SYS_FUNCTIONS.contains(&part)),
+                "RCC" => RESERVED_RCC_FUNCTIONS.contains(&signal.function.as_str()),
+                _ => false,
+            })
+            .map(|signal| signal.function)
+            .collect::<Vec<_>>();
+        if self.name.contains("BOOT0") {
+            functions.push("BOOT0".to_string());
+        }
+        functions
+    }
+
+    /// Return the GPIO port and pin number (e.g. ('C', 14) for
+    /// "PC14-OSC32_IN"), if this is a GPIO pin.
+    pub fn get_gpio(&self) -> Option<(char, u8)> {
+        if self.pin_type != "I/O" {
+            return None;
+        }
+        let captures = GPIO_PIN.captures(&self.name)?;
+        let port = captures.get(1)?.as_str().chars().next()?;
+        let number = captures.get(2)?.as_str().parse().ok()?;
+        if number < 16 {
+            Some((port, number))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Voltage {
+    min: String,
+    max: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Temperature {
+    min: String,
+    max: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,7 +258,97 @@ pub struct IP {
 }
 
 impl IP {
+    pub fn get_instance_name(&self) -> &str {
+        &self.instance_name
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
     pub fn get_version(&self) -> &str {
         &self.version
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(name: &str, pin_type: &str) -> Pin {
+        Pin {
+            name: name.to_string(),
+            pin_type: pin_type.to_string(),
+            signals: vec![],
+        }
+    }
+
+    fn pin_with_signals(name: &str, signals: &[&str]) -> Pin {
+        Pin {
+            signals: signals
+                .iter()
+                .map(|signal| PinSignal {
+                    name: signal.to_string(),
+                })
+                .collect(),
+            ..pin(name, "I/O")
+        }
+    }
+
+    #[test]
+    fn test_pin_get_gpio() {
+        assert_eq!(pin("PA0", "I/O").get_gpio(), Some(('A', 0)));
+        assert_eq!(pin("PC14-OSC32_IN", "I/O").get_gpio(), Some(('C', 14)));
+        assert_eq!(pin("PA13 (JTMS-SWDIO)", "I/O").get_gpio(), Some(('A', 13)));
+        assert_eq!(pin("PA0_C", "MonoIO").get_gpio(), None);
+        assert_eq!(pin("PB8-BOOT0", "Boot").get_gpio(), None);
+        assert_eq!(pin("VDD", "Power").get_gpio(), None);
+        assert_eq!(
+            pin("PC14-OSC32_IN", "I/O").get_gpio_name(),
+            Some("PC14".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pin_get_dual_pad_name() {
+        assert_eq!(pin("PA0_C", "MonoIO").get_dual_pad_name(), Some("PA0_C"));
+        assert_eq!(pin("PC3_C", "MonoIO").get_dual_pad_name(), Some("PC3_C"));
+        assert_eq!(pin("PA0", "I/O").get_dual_pad_name(), None);
+        assert_eq!(pin("PC14-OSC32_IN", "I/O").get_dual_pad_name(), None);
+    }
+
+    #[test]
+    fn test_pin_get_reserved_functions() {
+        assert_eq!(
+            pin_with_signals("PA13", &["SYS_JTMS-SWDIO", "USART1_TX"]).get_reserved_functions(),
+            vec!["JTMS-SWDIO"]
+        );
+        assert_eq!(
+            pin_with_signals("PA14", &["SYS_SWCLK"]).get_reserved_functions(),
+            vec!["SWCLK"]
+        );
+        assert_eq!(
+            pin_with_signals("PC14-OSC32_IN", &["RCC_OSC32_IN"]).get_reserved_functions(),
+            vec!["OSC32_IN"]
+        );
+        assert_eq!(
+            pin_with_signals("PA14-BOOT0", &["SYS_SWCLK"]).get_reserved_functions(),
+            vec!["SWCLK", "BOOT0"]
+        );
+        assert_eq!(
+            pin_with_signals("PB3", &["SYS_JTDO-TRACESWO", "SPI1_SCK"]).get_reserved_functions(),
+            vec!["JTDO-TRACESWO"]
+        );
+        assert_eq!(
+            pin_with_signals("PA15", &["SYS_JTDI"]).get_reserved_functions(),
+            vec!["JTDI"]
+        );
+        assert_eq!(
+            pin_with_signals("PB4", &["SYS_NJTRST"]).get_reserved_functions(),
+            vec!["NJTRST"]
+        );
+        assert!(pin_with_signals("PA0", &["SYS_WKUP1", "RCC_MCO"])
+            .get_reserved_functions()
+            .is_empty());
+    }
+}
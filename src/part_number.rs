@@ -0,0 +1,469 @@
+use std::fmt;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    // The type has one or two letters, except for the STM32WBA. A greedy
+    // `[A-Z]+` would swallow the core of e.g. "STM32WLE5JCIx".
+    static ref PART_NUMBER: Regex = Regex::new(
+        "^STM32(WBA|[A-Z]{1,2})([0-9A-Z]+?)([A-Z])([0-9A-Z])([A-Z])([x0-9])([A-Z]*)$"
+    )
+    .unwrap();
+}
+
+/// Known exceptions from the naming scheme.
+///
+/// The database values of part numbers starting with one of these prefixes
+/// are not checked against the decoded part number.
+///
+/// - `STM32MP1`: Microprocessors without internal flash. The letters following
+///   the line encode the feature set and the security option instead of the
+///   pin count and flash size.
+const KNOWN_EXCEPTIONS: &[&str] = &["STM32MP1"];
+
+/// Generic temperature codes of ordering codes and the matching ranges (min,
+/// max) in °C.
+///
+/// Not every family is sold in every grade, and the database does not list
+/// the grades that can be ordered. The codes derived from this table are
+/// therefore only candidates and may not exist for a given part.
+const TEMPERATURE_GRADES: &[(char, (i32, i32))] =
+    &[('6', (-40, 85)), ('7', (-40, 105)), ('3', (-40, 125))];
+
+/// Return the flash size in kbytes for a flash size code (e.g. 'B' -> 128).
+pub fn flash_code_to_size(code: char) -> Option<u32> {
+    match code {
+        '3' => Some(8),
+        '4' => Some(16),
+        '6' => Some(32),
+        '8' => Some(64),
+        'B' => Some(128),
+        'Z' => Some(192),
+        'C' => Some(256),
+        'D' => Some(384),
+        'E' => Some(512),
+        'F' => Some(768),
+        'G' => Some(1024),
+        'H' => Some(1536),
+        'I' => Some(2048),
+        _ => None,
+    }
+}
+
+/// Return the pin count of a package (e.g. "LQFP48" -> 48).
+pub fn package_pin_count(package: &str) -> Option<u32> {
+    package
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|digits| !digits.is_empty())
+        .and_then(|digits| digits.parse().ok())
+}
+
+/// Return the possible pin counts for a pin count code (e.g. 'K' -> 32).
+///
+/// Some codes are used for packages with slightly different pin counts (e.g.
+/// 'R' for LQFP64 and WLCSP66).
+pub fn pin_count_code_to_pin_counts(code: char) -> &'static [u32] {
+    match code {
+        'D' => &[14],
+        'Y' => &[18, 19],
+        'F' => &[20],
+        'E' => &[24, 25],
+        'G' => &[28],
+        'K' => &[32],
+        'T' => &[36],
+        'H' => &[40],
+        'S' => &[44],
+        'C' => &[48, 49],
+        'U' => &[63],
+        'R' => &[64, 66, 68],
+        'J' => &[72],
+        'M' => &[80, 81],
+        'O' => &[90],
+        'V' => &[100],
+        'Q' => &[132],
+        'Z' => &[143, 144],
+        'A' => &[169],
+        'I' => &[176, 201],
+        'B' => &[208],
+        'N' => &[216],
+        'X' => &[240, 256, 265],
+        _ => &[],
+    }
+}
+
+/// Return the possible package names (without pin count) for a package code
+/// (e.g. 'T' -> LQFP).
+pub fn package_code_to_packages(code: char) -> &'static [&'static str] {
+    match code {
+        'T' => &["LQFP"],
+        'U' => &["UFQFPN", "VFQFPN"],
+        'Y' => &["WLCSP"],
+        'P' => &["TSSOP"],
+        'H' => &["TFBGA", "LFBGA", "UFBGA"],
+        'K' => &["UFBGA"],
+        'I' => &["UFBGA", "TFBGA"],
+        'M' => &["SO"],
+        _ => &[],
+    }
+}
+
+/// A decoded ST part number (e.g. "STM32L071KBTx").
+///
+/// |Part |Field        |
+/// |-----|-------------|
+/// |STM32|-            |
+/// | L   |`type_`      |
+/// | 0   |`core`       |
+/// | 71  |`line`       |
+/// | K   |`pin_count`  |
+/// | B   |`flash`      |
+/// | T   |`package`    |
+/// | x   |`temperature`|
+/// |-----|-------------|
+///
+/// The temperature code is "x" for ref names in the CubeMX database, since it
+/// only varies between orderable parts. Some part numbers have an additional
+/// suffix (e.g. the "S" in "STM32L031G6UxS").
+///
+/// Note that for some families (e.g. STM32WB) the core is not encoded in the
+/// name. The first character after the type is still decoded as `core` (e.g.
+/// the "E" in "STM32WLE5JCIx").
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartNumber {
+    pub type_: String,
+    pub core: char,
+    pub line: String,
+    pub pin_count: char,
+    pub flash: char,
+    pub package: char,
+    pub temperature: char,
+    pub suffix: String,
+}
+
+impl PartNumber {
+    /// Decode a part number.
+    pub fn parse(name: &str) -> Option<Self> {
+        let captures = PART_NUMBER.captures(name)?;
+        let get = |i: usize| captures.get(i).unwrap().as_str();
+        let get_char = |i: usize| get(i).chars().next().unwrap();
+        let mut core_line = get(2).chars();
+        Some(PartNumber {
+            type_: get(1).to_string(),
+            core: core_line.next()?,
+            line: core_line.collect(),
+            pin_count: get_char(3),
+            flash: get_char(4),
+            package: get_char(5),
+            temperature: get_char(6),
+            suffix: get(7).to_string(),
+        })
+    }
+
+    /// Return the flash size in kbytes.
+    pub fn flash_size(&self) -> Option<u32> {
+        flash_code_to_size(self.flash)
+    }
+
+    /// Return the ordering codes of the temperature grades of a ref name (e.g.
+    /// "STM32L071KBT6" for "STM32L071KBTx") with their maximum temperature in
+    /// °C.
+    ///
+    /// Only grades within `range` (the range from the MCU XML file) are
+    /// returned. Full ordering codes have no other grades.
+    ///
+    /// The ordering codes are synthesized from `TEMPERATURE_GRADES` and are
+    /// not checked against the database, so some of them (e.g. a `7` grade
+    /// of a family only sold as `6` and `3`) may not be orderable.
+    pub fn temperature_grades(&self, range: (i32, i32)) -> Vec<(String, i32)> {
+        if self.temperature != 'x' {
+            return vec![];
+        }
+        TEMPERATURE_GRADES
+            .iter()
+            .filter(|(_, (min, max))| *min >= range.0 && *max <= range.1)
+            .map(|(code, (_, max))| {
+                let part = PartNumber {
+                    temperature: *code,
+                    ..self.clone()
+                };
+                (part.to_string(), *max)
+            })
+            .collect()
+    }
+
+    /// Check the decoded part number against the values from the database.
+    ///
+    /// Returns a list of inconsistencies. Unknown codes are not checked, and
+    /// neither are the known exceptions (see `KNOWN_EXCEPTIONS`).
+    /// `flash_sizes` are the flash sizes listed in the MCU XML file.
+    pub fn check(&self, package_name: &str, flash_sizes: &[u32]) -> Vec<String> {
+        let mut warnings = vec![];
+        let name = format!("STM32{}{}{}", self.type_, self.core, self.line);
+        if KNOWN_EXCEPTIONS
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            return warnings;
+        }
+
+        let packages = package_code_to_packages(self.package);
+        if !packages.is_empty() && !packages.iter().any(|p| package_name.starts_with(p)) {
+            warnings.push(format!(
+                "Package code {:?} does not match package {}",
+                self.package, package_name
+            ));
+        }
+
+        let pin_counts = pin_count_code_to_pin_counts(self.pin_count);
+        if let Some(pin_count) = package_pin_count(package_name) {
+            if !pin_counts.is_empty() && !pin_counts.contains(&pin_count) {
+                warnings.push(format!(
+                    "Pin count code {:?} does not match package {}",
+                    self.pin_count, package_name
+                ));
+            }
+        }
+
+        if let Some(decoded) = self.flash_size() {
+            if !flash_sizes.is_empty() && !flash_sizes.contains(&decoded) {
+                warnings.push(format!(
+                    "Flash code {:?} ({} kbytes) does not match flash sizes {:?}",
+                    self.flash, decoded, flash_sizes
+                ));
+            }
+        }
+
+        warnings
+    }
+}
+
+impl fmt::Display for PartNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "STM32{}{}{}{}{}{}{}{}",
+            self.type_,
+            self.core,
+            self.line,
+            self.pin_count,
+            self.flash,
+            self.package,
+            self.temperature,
+            self.suffix
+        )
+    }
+}
+
+/// Expand a MCU name in CubeMX range notation into the part numbers it
+/// covers.
+///
+/// Every parenthesized group contains dash separated alternatives, e.g.
+/// "STM32L071K(B-Z)Tx" covers "STM32L071KBTx" and "STM32L071KZTx". Note that
+/// the alternatives are not a range: "STM32L031C(4-6)Tx" does not cover
+/// "STM32L031C5Tx".
+pub fn expand_name(name: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut rest = name;
+    while let Some(start) = rest.find('(') {
+        let end = match rest[start..].find(')') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let prefix = &rest[..start];
+        parts = parts
+            .iter()
+            .flat_map(|part| {
+                rest[start + 1..end]
+                    .split('-')
+                    .map(move |alternative| format!("{}{}{}", part, prefix, alternative))
+            })
+            .collect();
+        rest = &rest[end + 1..];
+    }
+    for part in &mut parts {
+        part.push_str(rest);
+    }
+    parts
+}
+
+/// Expand a MCU name (see `expand_name`) and pair every part number with its
+/// flash size in kbytes.
+///
+/// The flash sizes are those listed in the MCU XML file. The flash size code
+/// of the part number is used to find the matching size. If that is not
+/// possible, the sizes are assigned in order.
+pub fn expand_flash_variants(name: &str, flash_sizes: &[u32]) -> Vec<(String, Option<u32>)> {
+    let parts = expand_name(name);
+    let in_order = parts.len() == flash_sizes.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let size = PartNumber::parse(&part)
+                .and_then(|decoded| decoded.flash_size())
+                .filter(|size| flash_sizes.contains(size))
+                .or_else(|| if in_order { Some(flash_sizes[i]) } else { None });
+            (part, size)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            PartNumber::parse("STM32L071KBTx").unwrap(),
+            PartNumber {
+                type_: "L".to_string(),
+                core: '0',
+                line: "71".to_string(),
+                pin_count: 'K',
+                flash: 'B',
+                package: 'T',
+                temperature: 'x',
+                suffix: "".to_string(),
+            }
+        );
+
+        let part = PartNumber::parse("STM32L031G6UxS").unwrap();
+        assert_eq!((part.line.as_str(), part.flash), ("31", '6'));
+        assert_eq!(part.suffix, "S");
+
+        let part = PartNumber::parse("STM32L4R5ZITx").unwrap();
+        assert_eq!((part.core, part.line.as_str()), ('4', "R5"));
+        assert_eq!((part.pin_count, part.flash), ('Z', 'I'));
+
+        let part = PartNumber::parse("STM32G0B1RETx").unwrap();
+        assert_eq!((part.core, part.line.as_str()), ('0', "B1"));
+
+        let part = PartNumber::parse("STM32WB55RGVx").unwrap();
+        assert_eq!(part.type_, "WB");
+        assert_eq!((part.core, part.line.as_str()), ('5', "5"));
+        assert_eq!(part.package, 'V');
+
+        let part = PartNumber::parse("STM32WLE5JCIx").unwrap();
+        assert_eq!(part.type_, "WL");
+        assert_eq!((part.core, part.line.as_str()), ('E', "5"));
+        assert_eq!((part.pin_count, part.flash, part.package), ('J', 'C', 'I'));
+
+        let part = PartNumber::parse("STM32WBA52CGUx").unwrap();
+        assert_eq!(part.type_, "WBA");
+        assert_eq!((part.core, part.line.as_str()), ('5', "2"));
+        assert_eq!((part.pin_count, part.flash), ('C', 'G'));
+
+        let part = PartNumber::parse("STM32F030C6T6").unwrap();
+        assert_eq!(part.temperature, '6');
+
+        assert!(PartNumber::parse("STM32L071K(B-Z)Tx").is_none());
+        assert!(PartNumber::parse("STM8L051F3P6").is_none());
+    }
+
+    #[test]
+    fn test_temperature_grades() {
+        let part = PartNumber::parse("STM32L071KBTx").unwrap();
+        assert_eq!(part.to_string(), "STM32L071KBTx");
+        assert_eq!(
+            part.temperature_grades((-40, 125)),
+            vec![
+                ("STM32L071KBT6".to_string(), 85),
+                ("STM32L071KBT7".to_string(), 105),
+                ("STM32L071KBT3".to_string(), 125),
+            ]
+        );
+        assert_eq!(
+            part.temperature_grades((-40, 85)),
+            vec![("STM32L071KBT6".to_string(), 85)]
+        );
+
+        let part = PartNumber::parse("STM32L031G6UxS").unwrap();
+        assert_eq!(
+            part.temperature_grades((-40, 85)),
+            vec![("STM32L031G6U6S".to_string(), 85)]
+        );
+
+        let part = PartNumber::parse("STM32F030C6T6").unwrap();
+        assert!(part.temperature_grades((-40, 85)).is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let part = PartNumber::parse("STM32L071KBTx").unwrap();
+        assert!(part.check("LQFP32", &[128]).is_empty());
+        assert_eq!(part.check("UFQFPN32", &[128]).len(), 1);
+        assert_eq!(part.check("LQFP48", &[128]).len(), 1);
+        assert_eq!(part.check("LQFP32", &[192]).len(), 1);
+
+        // Any of several listed flash sizes matches
+        let part = PartNumber::parse("STM32L071KZTx").unwrap();
+        assert!(part.check("LQFP32", &[128, 192]).is_empty());
+
+        // Unknown values are not checked
+        assert!(part.check("LQFP", &[]).is_empty());
+
+        // Known exceptions are not checked
+        let part = PartNumber::parse("STM32MP157CAAx").unwrap();
+        assert!(part.check("LFBGA448", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_expand_name() {
+        assert_eq!(expand_name("STM32L071KBTx"), vec!["STM32L071KBTx"]);
+        assert_eq!(
+            expand_name("STM32L071K(B-Z)Tx"),
+            vec!["STM32L071KBTx", "STM32L071KZTx"]
+        );
+        assert_eq!(
+            expand_name("STM32G071R(6-8-B)Tx"),
+            vec!["STM32G071R6Tx", "STM32G071R8Tx", "STM32G071RBTx"]
+        );
+        assert_eq!(
+            expand_name("STM32F0(30-31)C(4-6)Tx"),
+            vec![
+                "STM32F030C4Tx",
+                "STM32F030C6Tx",
+                "STM32F031C4Tx",
+                "STM32F031C6Tx"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_flash_variants() {
+        // Sizes matched by flash code
+        assert_eq!(
+            expand_flash_variants("STM32L071K(B-Z)Tx", &[192, 128]),
+            vec![
+                ("STM32L071KBTx".to_string(), Some(128)),
+                ("STM32L071KZTx".to_string(), Some(192)),
+            ]
+        );
+
+        // Flash code after a three letter type
+        assert_eq!(
+            expand_flash_variants("STM32WBA52C(G-E)Ux", &[512, 1024]),
+            vec![
+                ("STM32WBA52CGUx".to_string(), Some(1024)),
+                ("STM32WBA52CEUx".to_string(), Some(512)),
+            ]
+        );
+
+        // Unknown flash code, sizes assigned in order
+        assert_eq!(
+            expand_flash_variants("STM32L071K(X-Y)Tx", &[128, 192]),
+            vec![
+                ("STM32L071KXTx".to_string(), Some(128)),
+                ("STM32L071KYTx".to_string(), Some(192)),
+            ]
+        );
+
+        // Flash size not listed
+        assert_eq!(
+            expand_flash_variants("STM32L071KBTx", &[]),
+            vec![("STM32L071KBTx".to_string(), None)]
+        );
+    }
+}
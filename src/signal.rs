@@ -0,0 +1,216 @@
+/// Peripherals that contain an underscore in their name.
+///
+/// All other peripheral names are terminated by the first underscore in a
+/// signal name.
+const MULTI_WORD_PERIPHERALS: &[&str] = &["USB_OTG_FS", "USB_OTG_HS", "USB_DRD_FS"];
+
+/// A pin signal (e.g. "USART1_TX" or "TIM1_CH2N"), split into its parts.
+///
+/// Examples:
+///
+/// |Signal          |Peripheral  |Instance|Function    |Index|
+/// |----------------|------------|--------|------------|-----|
+/// |`USART1_TX`     |`USART`     |1       |`TX`        |-    |
+/// |`TIM1_CH2N`     |`TIM`       |1       |`CH2N`      |2    |
+/// |`USB_OTG_FS_DP` |`USB_OTG_FS`|-       |`DP`        |-    |
+/// |`TSC_G5_IO3`    |`TSC`       |-       |`G5_IO3`    |3    |
+/// |`ADC1_IN5`      |`ADC`       |1       |`IN5`       |5    |
+/// |`RCC_OSC32_IN`  |`RCC`       |-       |`OSC32_IN`  |-    |
+/// |----------------|------------|--------|------------|-----|
+///
+/// Note that shared peripherals are named by ST using all of their instance
+/// numbers, e.g. `ADC12_IN5` on the STM32F3 refers to both ADC1 and ADC2. In
+/// that case the instance is parsed as `12`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signal {
+    /// The peripheral type (e.g. "USART" or "USB_OTG_FS").
+    pub peripheral: String,
+    /// The peripheral instance number, if any (e.g. 1 for "USART1").
+    pub instance: Option<u32>,
+    /// Everything following the peripheral instance (e.g. "TX" or "G5_IO3").
+    pub function: String,
+    /// The number in the last part of the function, if any (e.g. 2 for
+    /// "CH2N" or 3 for "G5_IO3").
+    pub index: Option<u32>,
+}
+
+impl Signal {
+    /// Parse a signal name.
+    ///
+    /// Returns `None` if the name does not consist of a peripheral and a
+    /// function (e.g. "VDD" or "CEC").
+    pub fn parse(name: &str) -> Option<Self> {
+        let (peripheral_instance, function) = MULTI_WORD_PERIPHERALS
+            .iter()
+            .find(|p| name.starts_with(*p) && name[p.len()..].starts_with('_'))
+            .map(|p| (&name[..p.len()], &name[p.len() + 1..]))
+            .or_else(|| {
+                let mut parts = name.splitn(2, '_');
+                Some((parts.next()?, parts.next()?))
+            })?;
+        if function.is_empty() {
+            return None;
+        }
+
+        // Split off the instance number
+        let base_len = peripheral_instance
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .len();
+        if base_len == 0 {
+            return None;
+        }
+        let peripheral = &peripheral_instance[..base_len];
+        let instance = peripheral_instance[base_len..].parse().ok();
+
+        // Find the index in the last part of the function
+        let index = function
+            .rsplit('_')
+            .next()
+            .unwrap()
+            .split(|c: char| !c.is_ascii_digit())
+            .find(|digits| !digits.is_empty())
+            .and_then(|digits| digits.parse().ok());
+
+        Some(Signal {
+            peripheral: peripheral.to_string(),
+            instance,
+            function: function.to_string(),
+            index,
+        })
+    }
+
+    /// Return the name of the peripheral instance (e.g. "USART1" or
+    /// "USB_OTG_FS").
+    pub fn instance_name(&self) -> String {
+        match self.instance {
+            Some(instance) => format!("{}{}", self.peripheral, instance),
+            None => self.peripheral.clone(),
+        }
+    }
+}
+
+/// Convert a pin function to the name of the matching pin trait (e.g. "INP" to
+/// "InpPin" or "BK1_IO0" to "Bk1Io0Pin").
+pub fn function_to_trait(function: &str) -> String {
+    let mut res = String::new();
+    for part in function.split('_') {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            res.push(first.to_ascii_uppercase());
+            res.push_str(&chars.as_str().to_lowercase());
+        }
+    }
+    res.push_str("Pin");
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(
+        name: &str,
+        peripheral: &str,
+        instance: Option<u32>,
+        function: &str,
+        index: Option<u32>,
+    ) {
+        assert_eq!(
+            Signal::parse(name),
+            Some(Signal {
+                peripheral: peripheral.to_string(),
+                instance,
+                function: function.to_string(),
+                index,
+            }),
+            "Signal {:?}",
+            name
+        );
+    }
+
+    #[test]
+    fn test_parse_serial() {
+        check("USART1_TX", "USART", Some(1), "TX", None);
+        check("USART10_RX", "USART", Some(10), "RX", None);
+        check("UART4_RX", "UART", Some(4), "RX", None);
+        check("LPUART1_RTS_DE", "LPUART", Some(1), "RTS_DE", None);
+        check("SPI2_MOSI", "SPI", Some(2), "MOSI", None);
+        check("I2S2_ext_SD", "I2S", Some(2), "ext_SD", None);
+        check("I2S_CKIN", "I2S", None, "CKIN", None);
+        check("I2C1_SCL", "I2C", Some(1), "SCL", None);
+        check("FMPI2C1_SDA", "FMPI2C", Some(1), "SDA", None);
+    }
+
+    #[test]
+    fn test_parse_timers() {
+        check("TIM1_CH2N", "TIM", Some(1), "CH2N", Some(2));
+        check("TIM15_BKIN", "TIM", Some(15), "BKIN", None);
+        check("TIM1_BKIN2", "TIM", Some(1), "BKIN2", Some(2));
+        check("LPTIM1_IN1", "LPTIM", Some(1), "IN1", Some(1));
+        check("HRTIM1_CHA1", "HRTIM", Some(1), "CHA1", Some(1));
+    }
+
+    #[test]
+    fn test_parse_multi_word_peripherals() {
+        check("USB_OTG_FS_DP", "USB_OTG_FS", None, "DP", None);
+        check("USB_OTG_HS_ULPI_D7", "USB_OTG_HS", None, "ULPI_D7", Some(7));
+        check("USB_DRD_FS_DP", "USB_DRD_FS", None, "DP", None);
+        check("HDMI_CEC", "HDMI", None, "CEC", None);
+        check("USB_DM", "USB", None, "DM", None);
+    }
+
+    #[test]
+    fn test_parse_analog() {
+        check("ADC_IN0", "ADC", None, "IN0", Some(0));
+        check("ADC1_IN5", "ADC", Some(1), "IN5", Some(5));
+        check("ADC12_IN5", "ADC", Some(12), "IN5", Some(5));
+        check("ADC1_INP16", "ADC", Some(1), "INP16", Some(16));
+        check("DAC_OUT1", "DAC", None, "OUT1", Some(1));
+        check("COMP2_INM", "COMP", Some(2), "INM", None);
+        check("OPAMP1_VINP", "OPAMP", Some(1), "VINP", None);
+    }
+
+    #[test]
+    fn test_parse_misc() {
+        check("TSC_G5_IO3", "TSC", None, "G5_IO3", Some(3));
+        check("SAI1_SD_A", "SAI", Some(1), "SD_A", None);
+        check("QUADSPI_BK1_IO0", "QUADSPI", None, "BK1_IO0", Some(0));
+        check("SDMMC1_D0", "SDMMC", Some(1), "D0", Some(0));
+        check("FMC_A15", "FMC", None, "A15", Some(15));
+        check("ETH_RXD1", "ETH", None, "RXD1", Some(1));
+        check("SYS_JTMS-SWDIO", "SYS", None, "JTMS-SWDIO", None);
+        check("SYS_WKUP1", "SYS", None, "WKUP1", Some(1));
+        check("RCC_OSC32_IN", "RCC", None, "OSC32_IN", None);
+        check("RCC_MCO_1", "RCC", None, "MCO_1", Some(1));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(Signal::parse("VDD"), None);
+        assert_eq!(Signal::parse("CEC"), None);
+        assert_eq!(Signal::parse("SPI1_"), None);
+        assert_eq!(Signal::parse("_TX"), None);
+        assert_eq!(Signal::parse("12_TX"), None);
+    }
+
+    #[test]
+    fn test_instance_name() {
+        assert_eq!(
+            Signal::parse("USART1_TX").unwrap().instance_name(),
+            "USART1"
+        );
+        assert_eq!(Signal::parse("ADC_IN0").unwrap().instance_name(), "ADC");
+        assert_eq!(
+            Signal::parse("USB_OTG_FS_DP").unwrap().instance_name(),
+            "USB_OTG_FS"
+        );
+    }
+
+    #[test]
+    fn test_function_to_trait() {
+        assert_eq!(function_to_trait("INP"), "InpPin");
+        assert_eq!(function_to_trait("VOUT"), "VoutPin");
+        assert_eq!(function_to_trait("BK1_IO0"), "Bk1Io0Pin");
+        assert_eq!(function_to_trait("NE1"), "Ne1Pin");
+    }
+}